@@ -0,0 +1,13 @@
+// Records when the package was built, for --version. npm pack resets the
+// times of the files it packs, so the date is kept in build.json instead.
+const fs = require('fs');
+const path = require('path');
+
+const file = path.join(__dirname, '..', 'build.json');
+
+if (process.argv[2] == 'clean') {
+  if (fs.existsSync(file)) fs.unlinkSync(file);
+} else {
+  const stamp = { built_at: new Date().toISOString() };
+  fs.writeFileSync(file, JSON.stringify(stamp, null, 2) + '\n');
+}
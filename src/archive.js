@@ -0,0 +1,137 @@
+const zlib = require('zlib');
+
+// Builds archives of exported files in memory. Each entry is
+// { name, contents, mtime } with contents a Buffer.
+
+function octal(value, length) {
+  return value.toString(8).padStart(length - 1, '0') + '\0';
+}
+
+function tarHeader(name, size, mtime, type) {
+  const header = Buffer.alloc(512);
+  header.write(name.slice(0, 100), 0);
+  header.write(octal(0o644, 8), 100);
+  header.write(octal(0, 8), 108);
+  header.write(octal(0, 8), 116);
+  header.write(octal(size, 12), 124);
+  header.write(octal(Math.floor(mtime.getTime() / 1000), 12), 136);
+  header.write(type, 156);
+  header.write('ustar\u000000', 257);
+  // The checksum is computed with its own field filled with spaces
+  header.fill(' ', 148, 156);
+  let checksum = 0;
+  for (let i = 0; i < 512; i++) checksum += header[i];
+  header.write(checksum.toString(8).padStart(6, '0') + '\0 ', 148);
+  return header;
+}
+
+function padded(contents) {
+  const padding = (512 - (contents.length % 512)) % 512;
+  return Buffer.concat([contents, Buffer.alloc(padding)]);
+}
+
+// A pax header carrying names too long for the ustar name field
+function paxHeader(name, mtime) {
+  const field = ' path=' + name + '\n';
+  // The record length includes its own digits
+  let length = Buffer.byteLength(field);
+  length += String(length + String(length).length).length;
+  const record = Buffer.from(length + field);
+  return Buffer.concat([
+    tarHeader('PaxHeader', record.length, mtime, 'x'),
+    padded(record),
+  ]);
+}
+
+function tarArchive(entries) {
+  const blocks = [];
+  entries.forEach((entry) => {
+    if (Buffer.byteLength(entry.name) > 100) {
+      blocks.push(paxHeader(entry.name, entry.mtime));
+    }
+    blocks.push(tarHeader(entry.name, entry.contents.length, entry.mtime, '0'));
+    blocks.push(padded(entry.contents));
+  });
+  // Two empty blocks end the archive
+  blocks.push(Buffer.alloc(1024));
+  return Buffer.concat(blocks);
+}
+
+const CRC_TABLE = Array.from({ length: 256 }, (value, n) => {
+  let c = n;
+  for (let k = 0; k < 8; k++) c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+  return c >>> 0;
+});
+
+function crc32(buffer) {
+  let crc = 0xffffffff;
+  for (let i = 0; i < buffer.length; i++) {
+    crc = CRC_TABLE[(crc ^ buffer[i]) & 0xff] ^ (crc >>> 8);
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+// Zip stores local times in MS-DOS format, at two second resolution
+function dosDateTime(date) {
+  return {
+    time:
+      (date.getHours() << 11) |
+      (date.getMinutes() << 5) |
+      Math.floor(date.getSeconds() / 2),
+    date:
+      ((date.getFullYear() - 1980) << 9) |
+      ((date.getMonth() + 1) << 5) |
+      date.getDate(),
+  };
+}
+
+function zipArchive(entries) {
+  const local = [];
+  const central = [];
+  let offset = 0;
+  entries.forEach((entry) => {
+    const name = Buffer.from(entry.name);
+    const data = zlib.deflateRawSync(entry.contents);
+    const stamp = dosDateTime(entry.mtime);
+    // Version needed, flags (UTF-8 names), deflate, time, date, crc and sizes
+    const fields = Buffer.alloc(26);
+    fields.writeUInt16LE(20, 0);
+    fields.writeUInt16LE(0x800, 2);
+    fields.writeUInt16LE(8, 4);
+    fields.writeUInt16LE(stamp.time, 6);
+    fields.writeUInt16LE(stamp.date, 8);
+    fields.writeUInt32LE(crc32(entry.contents), 10);
+    fields.writeUInt32LE(data.length, 14);
+    fields.writeUInt32LE(entry.contents.length, 18);
+    fields.writeUInt16LE(name.length, 22);
+
+    const header = Buffer.concat([Buffer.from('PK\x03\x04', 'binary'), fields]);
+    local.push(header, name, data);
+
+    const record = Buffer.alloc(46);
+    record.write('PK\x01\x02', 0, 'binary');
+    // Made on Unix, so the external attributes hold Unix permissions
+    record.writeUInt16LE((3 << 8) | 20, 4);
+    fields.copy(record, 6);
+    record.writeUInt32LE((0o100644 << 16) >>> 0, 38);
+    record.writeUInt32LE(offset, 42);
+    central.push(record, name);
+    offset += header.length + name.length + data.length;
+  });
+
+  const directory = Buffer.concat(central);
+  const end = Buffer.alloc(22);
+  end.write('PK\x05\x06', 0, 'binary');
+  end.writeUInt16LE(entries.length, 8);
+  end.writeUInt16LE(entries.length, 10);
+  end.writeUInt32LE(directory.length, 12);
+  end.writeUInt32LE(offset, 16);
+  return Buffer.concat(local.concat(directory, end));
+}
+
+const ARCHIVES = {
+  tar: { extension: 'tar', build: tarArchive },
+  zip: { extension: 'zip', build: zipArchive },
+};
+
+module.exports = { ARCHIVES, ARCHIVE_NAMES: Object.keys(ARCHIVES) };
@@ -0,0 +1,70 @@
+// The account audit log, narrowed to changes to DNS records
+
+const DURATION_UNITS = { m: 60, h: 3600, d: 86400, w: 604800 };
+
+// Audit log actions for adding, changing and deleting records
+const DNS_ACTIONS = ['rec_add', 'rec_set', 'rec_del'];
+
+// The largest page the audit log API returns
+const PAGE_SIZE = 1000;
+
+// A date, or a duration such as 7d counted back from now
+function parseTime(text, now) {
+  const duration = /^(\d+)([mhdw])$/i.exec(text);
+  if (duration) {
+    const unit = DURATION_UNITS[duration[2].toLowerCase()];
+    return new Date(now.getTime() - Number(duration[1]) * unit * 1000);
+  }
+  const date = new Date(text);
+  if (isNaN(date.getTime())) {
+    throw new Error(
+      'Not a date or a duration such as 24h or 7d: ' + JSON.stringify(text)
+    );
+  }
+  return date;
+}
+
+function isDnsChange(entry) {
+  const action = entry.action ? entry.action.type : null;
+  const resource = entry.resource ? entry.resource.type : null;
+  return DNS_ACTIONS.includes(action) || /^dns[._]record$/i.test(resource);
+}
+
+// Every DNS change in the account between since and until, oldest first
+async function dnsAuditEntries(client, accountId, since, until) {
+  const entries = [];
+  let page = 1;
+  let results;
+  do {
+    const response = await client.get(
+      'accounts/' + accountId + '/audit_logs',
+      {
+        params: {
+          since: since.toISOString(),
+          before: until.toISOString(),
+          direction: 'asc',
+          per_page: PAGE_SIZE,
+          page: page,
+        },
+      }
+    );
+    if (!response.data.success) {
+      throw new Error(
+        response.data.errors.map((error) => error.message).join(', ')
+      );
+    }
+    results = response.data.result;
+    entries.push(...results.filter(isDnsChange));
+    page++;
+  } while (results.length == PAGE_SIZE);
+  return entries;
+}
+
+// The zone an entry changed, if the log says
+function entryZone(entry) {
+  return entry.metadata && entry.metadata.zone_name
+    ? entry.metadata.zone_name
+    : null;
+}
+
+module.exports = { parseTime, dnsAuditEntries, entryZone };
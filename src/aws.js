@@ -0,0 +1,188 @@
+const axios = require('axios');
+const crypto = require('crypto');
+const { httpConfig } = require('./http');
+
+// Credential values that point at AWS rather than holding the secret itself:
+//   arn:aws:secretsmanager:<region>:<account>:secret:<name>[#json-key]
+//   arn:aws:ssm:<region>:<account>:parameter/<name>
+//   ssm:<parameter-name>
+const SECRETS_MANAGER_ARN = /^arn:aws[\w-]*:secretsmanager:([\w-]+):/;
+const SSM_ARN = /^arn:aws[\w-]*:ssm:([\w-]+):/;
+
+// The credentials endpoint is local, so it answers quickly or not at all
+const CREDENTIALS_TIMEOUT = 2000;
+
+function isAwsReference(value) {
+  return Boolean(
+    value &&
+      (SECRETS_MANAGER_ARN.test(value) ||
+        SSM_ARN.test(value) ||
+        value.startsWith('ssm:'))
+  );
+}
+
+function sha256(data) {
+  return crypto.createHash('sha256').update(data).digest('hex');
+}
+
+function hmac(key, data) {
+  return crypto.createHmac('sha256', key).update(data).digest();
+}
+
+async function awsCredentials(env) {
+  if (env.AWS_ACCESS_KEY_ID && env.AWS_SECRET_ACCESS_KEY) {
+    return {
+      accessKeyId: env.AWS_ACCESS_KEY_ID,
+      secretAccessKey: env.AWS_SECRET_ACCESS_KEY,
+      sessionToken: env.AWS_SESSION_TOKEN,
+    };
+  }
+
+  // ECS tasks get role credentials from the container credentials endpoint
+  const url = env.AWS_CONTAINER_CREDENTIALS_FULL_URI
+    ? env.AWS_CONTAINER_CREDENTIALS_FULL_URI
+    : env.AWS_CONTAINER_CREDENTIALS_RELATIVE_URI
+    ? 'http://169.254.170.2' + env.AWS_CONTAINER_CREDENTIALS_RELATIVE_URI
+    : null;
+  if (!url) {
+    throw new Error(
+      'AWS credentials not found: set AWS_ACCESS_KEY_ID & AWS_SECRET_ACCESS_KEY or run inside Lambda/ECS'
+    );
+  }
+
+  const headers = {};
+  if (env.AWS_CONTAINER_AUTHORIZATION_TOKEN) {
+    headers.Authorization = env.AWS_CONTAINER_AUTHORIZATION_TOKEN;
+  }
+  let response;
+  try {
+    // Never through the proxy, the endpoint is only reachable from the task
+    response = await axios.get(url, {
+      headers: headers,
+      timeout: CREDENTIALS_TIMEOUT,
+      proxy: false,
+    });
+  } catch (error) {
+    throw new Error(
+      'Unable to get AWS credentials from ' + url + ': ' + error.message
+    );
+  }
+  return {
+    accessKeyId: response.data.AccessKeyId,
+    secretAccessKey: response.data.SecretAccessKey,
+    sessionToken: response.data.Token,
+  };
+}
+
+// Make a SigV4 signed call to an AWS JSON 1.1 API
+async function callAws(service, target, region, body, credentials) {
+  const host = service + '.' + region + '.amazonaws.com';
+  const payload = JSON.stringify(body);
+  const amzDate = new Date().toISOString().replace(/[:-]|\.\d{3}/g, '');
+  const date = amzDate.slice(0, 8);
+
+  const headers = {
+    'content-type': 'application/x-amz-json-1.1',
+    host: host,
+    'x-amz-date': amzDate,
+    'x-amz-target': target,
+  };
+  if (credentials.sessionToken) {
+    headers['x-amz-security-token'] = credentials.sessionToken;
+  }
+
+  const signedHeaders = Object.keys(headers).sort();
+  const canonicalRequest = [
+    'POST',
+    '/',
+    '',
+    signedHeaders.map((name) => name + ':' + headers[name] + '\n').join(''),
+    signedHeaders.join(';'),
+    sha256(payload),
+  ].join('\n');
+  const scope = [date, region, service, 'aws4_request'].join('/');
+  const stringToSign = [
+    'AWS4-HMAC-SHA256',
+    amzDate,
+    scope,
+    sha256(canonicalRequest),
+  ].join('\n');
+
+  let signingKey = 'AWS4' + credentials.secretAccessKey;
+  [date, region, service, 'aws4_request'].forEach((part) => {
+    signingKey = hmac(signingKey, part);
+  });
+  const signature = crypto
+    .createHmac('sha256', signingKey)
+    .update(stringToSign)
+    .digest('hex');
+
+  headers.Authorization =
+    'AWS4-HMAC-SHA256 Credential=' +
+    credentials.accessKeyId +
+    '/' +
+    scope +
+    ', SignedHeaders=' +
+    signedHeaders.join(';') +
+    ', Signature=' +
+    signature;
+  delete headers.host;
+
+  try {
+    const response = await axios.post(
+      'https://' + host + '/',
+      payload,
+      Object.assign({ headers: headers }, httpConfig())
+    );
+    return response.data;
+  } catch (error) {
+    const message =
+      error.response && error.response.data && error.response.data.message
+        ? error.response.data.message
+        : error.message;
+    throw new Error('AWS ' + target + ' failed: ' + message);
+  }
+}
+
+async function readAwsSecret(reference, env) {
+  const credentials = await awsCredentials(env);
+  const defaultRegion = env.AWS_REGION ? env.AWS_REGION : env.AWS_DEFAULT_REGION;
+  let match;
+
+  if ((match = reference.match(SECRETS_MANAGER_ARN))) {
+    // An optional #key selects one field of a JSON secret
+    const parts = reference.split('#');
+    const result = await callAws(
+      'secretsmanager',
+      'secretsmanager.GetSecretValue',
+      match[1],
+      { SecretId: parts[0] },
+      credentials
+    );
+    if (!parts[1]) {
+      return result.SecretString;
+    }
+    const value = JSON.parse(result.SecretString)[parts[1]];
+    if (!value) {
+      throw new Error('AWS secret ' + parts[0] + ' has no "' + parts[1] + '" key');
+    }
+    return value;
+  }
+
+  const region = (match = reference.match(SSM_ARN)) ? match[1] : defaultRegion;
+  if (!region) {
+    throw new Error('Set AWS_REGION to read SSM parameter ' + reference);
+  }
+  // GetParameter accepts either a parameter name or its full ARN
+  const name = match ? reference : reference.slice('ssm:'.length);
+  const result = await callAws(
+    'ssm',
+    'AmazonSSM.GetParameter',
+    region,
+    { Name: name, WithDecryption: true },
+    credentials
+  );
+  return result.Parameter.Value;
+}
+
+module.exports = { isAwsReference, readAwsSecret };
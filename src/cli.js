@@ -0,0 +1,696 @@
+const { FORMAT_NAMES } = require('./formats');
+const { ARCHIVE_NAMES } = require('./archive');
+const { CODEC_NAMES } = require('./compress');
+
+const PROGRAM = 'export-cloudflare-dns';
+
+// Subcommands, the first is used when none is given
+const COMMANDS = {
+  export: {
+    usage: 'export [zone] [options]',
+    description: 'Export DNS records for every zone (default)',
+    // An existing file is still taken as the env file older versions expected
+    args: [{ name: 'zone' }],
+  },
+  list: {
+    usage: 'list [options]',
+    description: 'Show zones with their ID, status, plan and record count',
+  },
+  init: {
+    usage: 'init [options]',
+    description: 'Create a config file by answering a few questions',
+  },
+  doctor: {
+    usage: 'doctor [options]',
+    description: 'Check the config, credentials and connection to Cloudflare',
+  },
+  credentials: {
+    usage: 'credentials <store|clear> [options]',
+    description: 'Save credentials to, or remove them from, the OS keyring',
+    args: [{ name: 'action', choices: ['store', 'clear'] }],
+  },
+  audit: {
+    usage: 'audit [options]',
+    description: 'Save the audit log of DNS record changes as NDJSON',
+  },
+  import: {
+    usage: 'import <zone> <file> [options]',
+    description: 'Create the records of an exported zone file in a zone',
+    args: [{ name: 'zone' }, { name: 'file' }],
+  },
+  sync: {
+    usage: 'sync <zone> <snapshot> [options]',
+    description: 'Add, change and delete records to match a zone file',
+    args: [{ name: 'zone' }, { name: 'snapshot' }],
+  },
+  diff: {
+    usage: 'diff <zone> <snapshot> [options]',
+    description: 'Show how a zone differs from a zone file, changing nothing',
+    args: [{ name: 'zone' }, { name: 'snapshot' }],
+  },
+  plan: {
+    usage: 'plan <zone> <snapshot> [options]',
+    description: 'Save the changes sync would make to a plan file',
+    args: [{ name: 'zone' }, { name: 'snapshot' }],
+  },
+  apply: {
+    usage: 'apply <plan> [options]',
+    description: 'Make the changes of a plan file, unless the zone changed',
+    args: [{ name: 'plan' }],
+  },
+  clone: {
+    usage: 'clone <zone> <target> [options]',
+    description: "Copy a zone's records to another zone, rewriting names",
+    args: [{ name: 'zone' }, { name: 'target' }],
+  },
+  migrate: {
+    usage: 'migrate <zone> --from-profile <a> --to-profile <b> [options]',
+    description: "Copy a zone's records to another account, adding the zone",
+    args: [{ name: 'zone' }],
+  },
+  help: {
+    usage: 'help [command]',
+    description: 'Show help for a command',
+    args: [{ name: 'command' }],
+  },
+};
+
+// Process exit codes, listed in the help so scripts can act on the outcome
+const EXIT_CODES = {
+  SUCCESS: 0,
+  FAILURE: 1,
+  CONFIG: 2,
+  AUTH: 3,
+  PARTIAL: 4,
+  NOTHING_TO_DO: 5,
+};
+
+const EXIT_CODE_DESCRIPTIONS = [
+  [EXIT_CODES.SUCCESS, 'Success'],
+  [EXIT_CODES.FAILURE, 'Export failed, no zones were exported'],
+  [EXIT_CODES.CONFIG, 'Invalid arguments, config file or credentials setup'],
+  [EXIT_CODES.AUTH, 'Cloudflare rejected the credentials or their permissions'],
+  [EXIT_CODES.PARTIAL, 'Some zones were exported, others failed'],
+  [EXIT_CODES.NOTHING_TO_DO, 'No zones matched the selection'],
+];
+
+// Supported command line flags, keyed by flag name. Options without a
+// commands list apply to every command.
+const OPTIONS = {
+  config: {
+    type: 'string',
+    value: 'path',
+    description: 'Path to the config file',
+  },
+  profile: {
+    type: 'string',
+    value: 'name',
+    description: 'Named profile from the config file',
+  },
+  'env-file': {
+    type: 'string',
+    value: 'path',
+    description: 'Load environment variables from this file instead of .env',
+  },
+  'api-token': {
+    type: 'string',
+    value: 'token',
+    description: 'Cloudflare API token',
+  },
+  'api-key': {
+    type: 'string',
+    value: 'key',
+    description: 'Cloudflare global API key',
+  },
+  email: {
+    type: 'string',
+    value: 'email',
+    description: 'Cloudflare account email',
+  },
+  'token-stdin': {
+    type: 'boolean',
+    description: 'Read the Cloudflare API token from stdin',
+  },
+  'vault-kv-version': {
+    type: 'string',
+    value: 'version',
+    choices: ['1', '2'],
+    description: 'KV version of the Vault secret, detected by default',
+  },
+  'api-endpoint': {
+    type: 'string',
+    value: 'url',
+    description: 'Base URL of the Cloudflare API, for gateways and mock servers',
+  },
+  proxy: {
+    type: 'string',
+    value: 'url',
+    description: 'HTTP(S) or SOCKS5 proxy, default HTTPS_PROXY or ALL_PROXY',
+  },
+  cacert: {
+    type: 'string',
+    value: 'pem',
+    description: 'Also trust the CA certificates in this PEM file',
+  },
+  insecure: {
+    type: 'boolean',
+    description: 'Skip TLS certificate checks, only for debugging',
+  },
+  timeout: {
+    type: 'integer',
+    value: 'seconds',
+    min: 1,
+    description: 'Give up on an API request after this long, default 60',
+  },
+  'connect-timeout': {
+    type: 'integer',
+    value: 'seconds',
+    min: 1,
+    description: 'Give up connecting to the API after this long',
+  },
+  'read-timeout': {
+    type: 'integer',
+    value: 'seconds',
+    min: 1,
+    description: 'Give up when the API sends nothing for this long',
+  },
+  format: {
+    type: 'string',
+    value: 'format',
+    commands: ['export'],
+    choices: FORMAT_NAMES,
+    description: 'Format of the exported files, default bind',
+  },
+  output: {
+    type: 'string',
+    value: 'dir',
+    commands: ['export', 'doctor', 'audit'],
+    description: 'Directory for the zone files, or - for stdout',
+  },
+  'local-output': {
+    type: 'boolean',
+    commands: ['export', 'doctor', 'audit'],
+    description: 'Write to ./domains as older versions did',
+  },
+  canonicalize: {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Sort and normalize records so exports diff cleanly',
+  },
+  'strip-comments': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Leave out the comments Cloudflare adds to zone files',
+  },
+  annotate: {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Keep proxied status and such as zone file comments',
+  },
+  template: {
+    type: 'string',
+    value: 'file',
+    commands: ['export'],
+    description: 'Template file for --format template',
+  },
+  'hosts-subdomain': {
+    type: 'string',
+    value: 'name',
+    commands: ['export'],
+    description: 'Only names in this subdomain for --format hosts',
+  },
+  'header-template': {
+    type: 'string',
+    value: 'file',
+    commands: ['export'],
+    description: 'Start each file with this template as a comment',
+  },
+  'split-by-type': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Write a directory per zone with a file per record type',
+  },
+  archive: {
+    type: 'string',
+    value: 'type',
+    commands: ['export'],
+    choices: ARCHIVE_NAMES,
+    description: 'Write the zone files and a manifest into one archive',
+  },
+  compress: {
+    type: 'string',
+    value: 'codec',
+    commands: ['export'],
+    choices: CODEC_NAMES,
+    description: 'Compress each written file, or the archive',
+  },
+  'compress-level': {
+    type: 'integer',
+    value: 'n',
+    min: 1,
+    commands: ['export'],
+    description: 'Compression level, default 6 for gzip and 3 for zstd',
+  },
+  'single-file': {
+    type: 'string',
+    value: 'path',
+    commands: ['export'],
+    description: 'Write every zone into this one file, or - for stdout',
+  },
+  'summary-json': {
+    type: 'string',
+    value: 'path',
+    commands: ['export'],
+    description: 'Write a JSON summary of the run to this file',
+  },
+  concurrency: {
+    type: 'integer',
+    value: 'n',
+    min: 1,
+    commands: [
+      'export',
+      'list',
+      'import',
+      'sync',
+      'apply',
+      'migrate',
+      'clone',
+    ],
+    description: 'Zones to fetch, or records to write, at a time, default 4',
+  },
+  'write-rate': {
+    type: 'integer',
+    value: 'n',
+    min: 1,
+    commands: ['import', 'sync', 'apply', 'migrate', 'clone'],
+    description: 'Most record writes a second, default 3',
+  },
+  'fail-fast': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Stop at the first zone that fails instead of continuing',
+  },
+  'dry-run': {
+    type: 'boolean',
+    commands: ['export', 'import', 'sync', 'migrate', 'clone'],
+    description: 'Show what would be written or changed, without doing it',
+  },
+  zones: {
+    type: 'string',
+    value: 'patterns',
+    commands: ['export', 'list', 'audit'],
+    description: 'Only include zones matching these comma separated globs',
+  },
+  'zones-regex': {
+    type: 'regex',
+    value: 'regex',
+    commands: ['export', 'list', 'audit'],
+    description: 'Only include zones whose name matches this regular expression',
+  },
+  'exclude-zones': {
+    type: 'string',
+    value: 'patterns',
+    commands: ['export', 'list', 'audit'],
+    description: 'Skip zones matching these comma separated globs',
+  },
+  'account-id': {
+    type: 'string',
+    value: 'id',
+    commands: ['export', 'list', 'audit'],
+    description: 'Only include zones belonging to this Cloudflare account',
+  },
+  status: {
+    type: 'string',
+    value: 'status',
+    commands: ['export', 'list', 'audit'],
+    choices: ['active', 'pending', 'moved', 'all'],
+    description: 'Only include zones with this status, default all',
+  },
+  'no-meta': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Do not write meta.json for each zone',
+  },
+  'include-settings': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export each zone\'s settings to settings.json',
+  },
+  'include-page-rules': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export each zone\'s page rules to pagerules.json',
+  },
+  'include-rulesets': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export redirect, transform and cache rules',
+  },
+  'include-waf': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export WAF custom rules and managed rule overrides',
+  },
+  'include-rate-limits': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export rate limiting rules, old and new',
+  },
+  'include-workers-routes': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export Workers routes and custom domains',
+  },
+  'include-dnssec': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export DNSSEC status and the DS record',
+  },
+  'include-nameservers': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export assigned and custom nameservers',
+  },
+  'include-email-routing': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export Email Routing rules and destinations',
+  },
+  'include-ssl': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also export certificate packs and SSL validation status',
+  },
+  'include-accounts': {
+    type: 'boolean',
+    commands: ['export'],
+    description: 'Also write the zones\' accounts to accounts.json',
+  },
+  'include-paused': {
+    type: 'boolean',
+    commands: ['export', 'list', 'audit'],
+    description: 'Include paused zones, which are skipped by default',
+  },
+  'include-pending': {
+    type: 'boolean',
+    commands: ['export', 'list', 'audit'],
+    description: 'Include pending zones, which are skipped by default',
+  },
+  plan: {
+    type: 'string',
+    value: 'plan',
+    commands: ['export', 'list', 'audit'],
+    choices: ['free', 'pro', 'business', 'enterprise'],
+    description: 'Only include zones on this Cloudflare plan',
+  },
+  prune: {
+    type: 'boolean',
+    commands: ['sync', 'plan'],
+    description: 'Also delete and replace records the snapshot does not have',
+  },
+  yes: {
+    type: 'boolean',
+    short: 'y',
+    commands: ['sync', 'migrate', 'clone'],
+    description: 'Apply the changes without asking first',
+  },
+  'from-profile': {
+    type: 'string',
+    value: 'name',
+    commands: ['migrate'],
+    description: 'Profile with the credentials of the account to copy from',
+  },
+  'to-profile': {
+    type: 'string',
+    value: 'name',
+    commands: ['migrate'],
+    description: 'Profile with the credentials of the account to copy to',
+  },
+  'to-account': {
+    type: 'string',
+    value: 'id',
+    commands: ['migrate'],
+    description: 'Account to add the zone to, if the credentials reach several',
+  },
+  rewrite: {
+    type: 'string',
+    value: 'from=to',
+    commands: ['clone'],
+    description: 'Names to replace while cloning, default <zone>=<target>',
+  },
+  out: {
+    type: 'string',
+    value: 'path',
+    commands: ['plan'],
+    description: 'Where to save the plan, default <zone>.plan.json',
+  },
+  since: {
+    type: 'string',
+    value: 'time',
+    commands: ['audit'],
+    description: 'Start of the audit window, a date or e.g. 7d ago, default 7d',
+  },
+  until: {
+    type: 'string',
+    value: 'time',
+    commands: ['audit'],
+    description: 'End of the audit window, a date or e.g. 1d ago, default now',
+  },
+  json: {
+    type: 'boolean',
+    commands: ['list'],
+    description: 'Print JSON instead of a table',
+  },
+  quiet: {
+    type: 'boolean',
+    short: 'q',
+    description: 'Only print errors',
+  },
+  verbose: {
+    type: 'count',
+    short: 'v',
+    description: 'Show per-zone progress, twice (-vv) for every API request',
+  },
+  'no-color': {
+    type: 'boolean',
+    description: 'Do not color the output, also set by NO_COLOR',
+  },
+  help: { type: 'boolean', short: 'h', description: 'Show this help' },
+  version: {
+    type: 'boolean',
+    short: 'V',
+    description: 'Show the version and environment details',
+  },
+  // For packagers, so it is left out of the help
+  'generate-man': {
+    type: 'boolean',
+    hidden: true,
+    description: 'Print a man page in roff format',
+  },
+};
+
+function camelCase(flag) {
+  return flag.replace(/-([a-z])/g, (match, letter) => letter.toUpperCase());
+}
+
+function optionsFor(command) {
+  return Object.keys(OPTIONS).filter(
+    (name) =>
+      !OPTIONS[name].commands || OPTIONS[name].commands.includes(command)
+  );
+}
+
+function findShort(letter) {
+  return Object.keys(OPTIONS).find((name) => OPTIONS[name].short == letter);
+}
+
+// Check and convert a flag value according to its option definition
+function convertValue(name, option, value) {
+  if (option.type == 'integer') {
+    const tooSmall = option.min !== undefined && Number(value) < option.min;
+    if (!/^-?\d+$/.test(value) || tooSmall) {
+      throw new Error(
+        'Option --' +
+          name +
+          ' expects a whole number' +
+          (option.min !== undefined ? ' of at least ' + option.min : '')
+      );
+    }
+    return Number(value);
+  }
+  if (option.type == 'regex') {
+    try {
+      new RegExp(value);
+    } catch (error) {
+      throw new Error('Option --' + name + ': ' + error.message);
+    }
+  }
+  if (option.choices && !option.choices.includes(value)) {
+    throw new Error(
+      'Option --' + name + ' must be one of: ' + option.choices.join(', ')
+    );
+  }
+  return value;
+}
+
+// Check a value from the config file against the option definition
+function configValue(name, value) {
+  const option = OPTIONS[name];
+  if (option.type == 'boolean') {
+    if (typeof value != 'boolean') {
+      throw new Error(name.replace(/-/g, '_') + ' must be true or false');
+    }
+    return value;
+  } else if (option.type == 'count') {
+    return Number(value);
+  } else if (Array.isArray(value)) {
+    // Lists are accepted wherever a flag takes a comma separated value
+    return value.map((item) => convertValue(name, option, String(item))).join(',');
+  }
+  return convertValue(name, option, String(value));
+}
+
+// Split argv into the command, flag values and positional arguments
+function parseArgs(argv) {
+  const options = {};
+  const positionals = [];
+  let command = null;
+
+  const setFlag = (name) => {
+    const key = camelCase(name);
+    options[key] =
+      OPTIONS[name].type == 'count' ? (options[key] || 0) + 1 : true;
+  };
+
+  for (let i = 0; i < argv.length; i++) {
+    const arg = argv[i];
+    if (arg == '--') {
+      positionals.push(...argv.slice(i + 1));
+      break;
+    } else if (/^-[A-Za-z]+$/.test(arg)) {
+      // Short flags, which may be bundled such as -vv
+      arg
+        .slice(1)
+        .split('')
+        .forEach((letter) => {
+          const name = findShort(letter);
+          if (!name) {
+            throw new Error('Unknown option -' + letter);
+          }
+          setFlag(name);
+        });
+      continue;
+    } else if (!arg.startsWith('--')) {
+      if (command === null && positionals.length == 0 && COMMANDS[arg]) {
+        command = arg;
+      } else {
+        positionals.push(arg);
+      }
+      continue;
+    }
+
+    const equals = arg.indexOf('=');
+    const name = arg.slice(2, equals > -1 ? equals : undefined);
+    const option = OPTIONS[name];
+    if (!option) {
+      throw new Error('Unknown option --' + name);
+    }
+
+    let value;
+    if (option.type == 'boolean' || option.type == 'count') {
+      setFlag(name);
+      continue;
+    } else if (equals > -1) {
+      value = arg.slice(equals + 1);
+    } else if (i + 1 < argv.length) {
+      value = argv[++i];
+    } else {
+      throw new Error('Option --' + name + ' requires a value');
+    }
+
+    value = convertValue(name, option, value);
+    const key = camelCase(name);
+    options[key] = option.multiple
+      ? (options[key] ? options[key] : []).concat(value)
+      : value;
+  }
+
+  command = command ? command : Object.keys(COMMANDS)[0];
+  validateCommand(command, options, positionals);
+
+  return { command, options, positionals };
+}
+
+function validateCommand(command, options, positionals) {
+  // Reject flags that belong to other commands
+  Object.keys(OPTIONS).forEach((name) => {
+    const given = options[camelCase(name)] !== undefined;
+    if (given && !optionsFor(command).includes(name)) {
+      throw new Error('Option --' + name + ' is not valid for ' + command);
+    }
+  });
+
+  const args = COMMANDS[command].args ? COMMANDS[command].args : [];
+  if (positionals.length > args.length) {
+    throw new Error('Unexpected argument "' + positionals[args.length] + '"');
+  }
+  args.forEach((spec, index) => {
+    const value = positionals[index];
+    if (value !== undefined && spec.choices && !spec.choices.includes(value)) {
+      throw new Error(
+        'Invalid ' +
+          spec.name +
+          ' "' +
+          value +
+          '", expected ' +
+          spec.choices.join(' or ')
+      );
+    }
+  });
+}
+
+function formatOption(name) {
+  const option = OPTIONS[name];
+  const flag =
+    (option.short ? '-' + option.short + ', ' : '    ') +
+    '--' +
+    name +
+    (option.value ? ' <' + option.value + '>' : '');
+  const choices = option.choices ? ' (' + option.choices.join('|') + ')' : '';
+  return '  ' + flag.padEnd(30) + ' ' + option.description + choices;
+}
+
+function helpText(command) {
+  const lines = [];
+  if (command && COMMANDS[command]) {
+    lines.push('Usage: ' + PROGRAM + ' ' + COMMANDS[command].usage, '');
+    lines.push(COMMANDS[command].description, '', 'Options:');
+    optionsFor(command)
+      .filter((name) => !OPTIONS[name].hidden)
+      .forEach((name) => lines.push(formatOption(name)));
+  } else {
+    lines.push('Usage: ' + PROGRAM + ' [command] [options]', '');
+    lines.push('Commands:');
+    Object.keys(COMMANDS).forEach((name) => {
+      lines.push('  ' + name.padEnd(30) + ' ' + COMMANDS[name].description);
+    });
+    lines.push('', 'Exit codes:');
+    EXIT_CODE_DESCRIPTIONS.forEach((entry) => {
+      lines.push('  ' + String(entry[0]).padEnd(30) + ' ' + entry[1]);
+    });
+    lines.push('', 'Run `' + PROGRAM + ' help <command>` for its options.');
+  }
+  return lines.join('\n') + '\n';
+}
+
+module.exports = {
+  PROGRAM,
+  COMMANDS,
+  EXIT_CODES,
+  EXIT_CODE_DESCRIPTIONS,
+  OPTIONS,
+  camelCase,
+  configValue,
+  parseArgs,
+  helpText,
+};
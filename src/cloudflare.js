@@ -0,0 +1,175 @@
+const axios = require('axios');
+const log = require('./log');
+const { httpConfig } = require('./http');
+
+const CLOUDFLARE_ENDPOINT = 'https://api.cloudflare.com/client/v4/';
+
+const AUTH_MODE_LABELS = {
+  token: 'API token',
+  key: 'global API key',
+  'service-key': 'user service key',
+};
+
+// CLOUDFLARE_API_ENDPOINT points the tool at a gateway or mock server instead
+function apiEndpoint() {
+  return process.env.CLOUDFLARE_API_ENDPOINT
+    ? process.env.CLOUDFLARE_API_ENDPOINT
+    : CLOUDFLARE_ENDPOINT;
+}
+
+// Credentials are read from process.env unless another set is given
+function createClient(env) {
+  const client = axios.create(
+    Object.assign(
+      {
+        baseURL: apiEndpoint(),
+        headers: Object.assign(
+          { 'Content-Type': 'application/json' },
+          authHeaders(env ? env : process.env)
+        ),
+      },
+      httpConfig()
+    )
+  );
+  traceRequests(client);
+  return client;
+}
+
+// Log each request with its status and duration at -vv
+function traceRequests(client) {
+  const describe = (config) => {
+    const query = config.params
+      ? Object.keys(config.params)
+          .map((key) => key + '=' + config.params[key])
+          .join('&')
+      : '';
+    const method = config.method.toUpperCase();
+    return method + ' ' + config.url + (query ? '?' + query : '');
+  };
+  const finish = (config, status) => {
+    const elapsed = Date.now() - config.startedAt;
+    log.debug(describe(config) + ' -> ' + status + ' (' + elapsed + 'ms)');
+  };
+
+  client.interceptors.request.use((config) => {
+    config.startedAt = Date.now();
+    return config;
+  });
+  client.interceptors.response.use(
+    (response) => {
+      finish(response.config, response.status);
+      return response;
+    },
+    (error) => {
+      if (error.config) {
+        const status = error.response ? error.response.status : error.code;
+        finish(error.config, status);
+      }
+      return Promise.reject(error);
+    }
+  );
+}
+
+function authMode(env) {
+  env = env ? env : process.env;
+  // Prefer a scoped API token over the global API key when both are set
+  if (env.CLOUDFLARE_API_TOKEN) {
+    return 'token';
+  } else if (env.CLOUDFLARE_API_KEY && env.CLOUDFLARE_USER_EMAIL) {
+    return 'key';
+  } else if (env.CLOUDFLARE_USER_SERVICE_KEY) {
+    return 'service-key';
+  }
+  return null;
+}
+
+// Service keys are issued with a version prefix, e.g. v1.0-...
+function isValidServiceKey(key) {
+  return /^v\d+\.\d+-\S+$/.test(key);
+}
+
+function authHeaders(env) {
+  if (authMode(env) == 'token') {
+    return { Authorization: 'Bearer ' + env.CLOUDFLARE_API_TOKEN };
+  } else if (authMode(env) == 'service-key') {
+    return { 'X-Auth-User-Service-Key': env.CLOUDFLARE_USER_SERVICE_KEY };
+  }
+  return {
+    'X-Auth-Email': env.CLOUDFLARE_USER_EMAIL,
+    'X-Auth-Key': env.CLOUDFLARE_API_KEY,
+  };
+}
+
+// Collect the messages from a failed Cloudflare API request
+function apiErrorMessages(error) {
+  if (error.response && error.response.data && error.response.data.errors) {
+    return error.response.data.errors.map((error) => error.message);
+  } else if (error.response) {
+    return ['HTTP ' + error.response.status];
+  }
+  return [error.message];
+}
+
+// Check the credentials work and can read zones and DNS records
+async function verifyCredentials(client) {
+  const mode = authMode();
+  try {
+    if (mode == 'token') {
+      const response = await client.get('user/tokens/verify');
+      if (response.data.result.status != 'active') {
+        throw new Error('API token is ' + response.data.result.status);
+      }
+    } else if (mode == 'key') {
+      await client.get('user');
+    }
+    // Service keys have no verify endpoint, the zone listing below checks them
+  } catch (error) {
+    throw new Error(
+      'Cloudflare rejected the ' +
+        AUTH_MODE_LABELS[mode] +
+        ': ' +
+        apiErrorMessages(error).join(', ')
+    );
+  }
+
+  let zones;
+  try {
+    const response = await client.get('zones', { params: { per_page: 1 } });
+    zones = response.data.result;
+  } catch (error) {
+    throw new Error(
+      'Unable to list zones, the credentials need the Zone:Read permission: ' +
+        apiErrorMessages(error).join(', ')
+    );
+  }
+  if (zones.length == 0) {
+    // Tokens without zone access see an empty list rather than an error
+    throw new Error(
+      'No zones are visible, the credentials need the Zone:Read permission for at least one zone'
+    );
+  }
+
+  try {
+    await client.get('zones/' + zones[0].id + '/dns_records', {
+      params: { per_page: 1 },
+    });
+  } catch (error) {
+    throw new Error(
+      'Unable to read DNS records for ' +
+        zones[0].name +
+        ', the credentials need the DNS:Read permission: ' +
+        apiErrorMessages(error).join(', ')
+    );
+  }
+}
+
+module.exports = {
+  CLOUDFLARE_ENDPOINT,
+  apiEndpoint,
+  AUTH_MODE_LABELS,
+  createClient,
+  authMode,
+  isValidServiceKey,
+  apiErrorMessages,
+  verifyCredentials,
+};
@@ -0,0 +1,41 @@
+const { spawnSync } = require('child_process');
+const zlib = require('zlib');
+
+// Without Node's own zstd support, fall back to the zstd command
+function zstdCommand(contents, level) {
+  const result = spawnSync('zstd', ['-q', '-c', '-' + level], {
+    input: contents,
+    maxBuffer: Infinity,
+  });
+  if (result.error) {
+    throw new Error(
+      'zstd compression needs Node.js 22.15 or later, or the zstd command: ' +
+        result.error.message
+    );
+  } else if (result.status !== 0) {
+    throw new Error('zstd failed: ' + result.stderr.toString().trim());
+  }
+  return result.stdout;
+}
+
+// Compression codecs for written files. Each compresses a Buffer at a level
+// within its range, or its default level, and names the extension it adds.
+const CODECS = {
+  gzip: {
+    extension: 'gz',
+    levels: { min: 1, max: 9, default: 6 },
+    compress: (contents, level) => zlib.gzipSync(contents, { level: level }),
+  },
+  zstd: {
+    extension: 'zst',
+    levels: { min: 1, max: 19, default: 3 },
+    compress(contents, level) {
+      if (!zlib.zstdCompressSync) return zstdCommand(contents, level);
+      const params = {};
+      params[zlib.constants.ZSTD_c_compressionLevel] = level;
+      return zlib.zstdCompressSync(contents, { params: params });
+    },
+  },
+};
+
+module.exports = { CODECS, CODEC_NAMES: Object.keys(CODECS) };
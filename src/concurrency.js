@@ -0,0 +1,52 @@
+// Run worker over items with at most limit calls in flight at once. Once a
+// worker fails no new items are started, and the first error is rethrown
+// after the calls already in flight have finished.
+async function runConcurrently(items, limit, worker) {
+  let next = 0;
+  let failed = false;
+  let firstError = null;
+
+  const runner = async () => {
+    while (!failed && next < items.length) {
+      const item = items[next++];
+      try {
+        await worker(item);
+      } catch (error) {
+        if (!failed) firstError = error;
+        failed = true;
+        return;
+      }
+    }
+  };
+
+  const runners = [];
+  for (let i = 0; i < Math.min(limit, items.length); i++) {
+    runners.push(runner());
+  }
+  await Promise.all(runners);
+  if (failed) throw firstError;
+}
+
+function sleep(ms) {
+  return new Promise((resolve) => setTimeout(resolve, ms));
+}
+
+// Spaces calls to wait() out to at most perSecond, and holds every caller
+// back for a while after pause(ms)
+function createThrottle(perSecond) {
+  const interval = 1000 / perSecond;
+  let next = 0;
+  return {
+    async wait() {
+      const now = Date.now();
+      const at = Math.max(now, next);
+      next = at + interval;
+      if (at > now) await sleep(at - now);
+    },
+    pause(ms) {
+      next = Math.max(next, Date.now() + ms);
+    },
+  };
+}
+
+module.exports = { runConcurrently, createThrottle };
@@ -0,0 +1,164 @@
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+
+const CONFIG_FILENAME = 'export-cloudflare-dns.toml';
+
+// Search order when no --config path is given
+function defaultConfigPaths() {
+  const configHome = process.env.XDG_CONFIG_HOME
+    ? process.env.XDG_CONFIG_HOME
+    : process.platform == 'win32' && process.env.APPDATA
+    ? process.env.APPDATA
+    : path.join(os.homedir(), '.config');
+
+  return [
+    CONFIG_FILENAME,
+    path.join(configHome, 'export-cloudflare-dns', 'config.toml'),
+  ];
+}
+
+// Where exports go when no --output directory is given
+function defaultOutputDir() {
+  const dataHome = process.env.XDG_DATA_HOME
+    ? process.env.XDG_DATA_HOME
+    : process.platform == 'win32' && process.env.LOCALAPPDATA
+    ? process.env.LOCALAPPDATA
+    : process.platform == 'darwin'
+    ? path.join(os.homedir(), 'Library', 'Application Support')
+    : path.join(os.homedir(), '.local', 'share');
+
+  return path.join(dataHome, 'export-cloudflare-dns', 'domains');
+}
+
+function loadConfig(configPath) {
+  const candidates = configPath ? [configPath] : defaultConfigPaths();
+  const found = candidates.find((candidate) => fs.existsSync(candidate));
+  if (!found) {
+    if (configPath) {
+      throw new Error('Config file not found: ' + configPath);
+    }
+    return { path: null, data: {} };
+  }
+
+  try {
+    return { path: found, data: parseToml(fs.readFileSync(found, 'utf8')) };
+  } catch (error) {
+    throw new Error(found + ': ' + error.message);
+  }
+}
+
+// Split a dotted key or table name, honoring quoted parts like zones."example.com"
+function splitKey(key, lineNumber) {
+  const parts = [];
+  const pattern = /\s*(?:"((?:[^"\\]|\\.)*)"|'([^']*)'|([A-Za-z0-9_-]+))\s*(\.|$)/y;
+  let match;
+  while (pattern.lastIndex < key.length && (match = pattern.exec(key))) {
+    parts.push(
+      match[1] !== undefined
+        ? JSON.parse('"' + match[1] + '"')
+        : match[2] !== undefined
+        ? match[2]
+        : match[3]
+    );
+    if (match[4] === '') break;
+  }
+  if (parts.length == 0 || pattern.lastIndex < key.length) {
+    throw new Error('line ' + lineNumber + ': invalid key ' + key);
+  }
+  return parts;
+}
+
+// Parse one value, returning it with the unparsed remainder of the line
+function parseValue(text, lineNumber) {
+  let match;
+  if ((match = text.match(/^"((?:[^"\\]|\\.)*)"/))) {
+    return [JSON.parse('"' + match[1] + '"'), text.slice(match[0].length)];
+  } else if ((match = text.match(/^'([^']*)'/))) {
+    return [match[1], text.slice(match[0].length)];
+  } else if ((match = text.match(/^(true|false)\b/))) {
+    return [match[1] == 'true', text.slice(match[0].length)];
+  } else if ((match = text.match(/^[+-]?\d[\d_]*(\.\d+)?/))) {
+    return [Number(match[0].replace(/_/g, '')), text.slice(match[0].length)];
+  } else if (text.startsWith('[')) {
+    const values = [];
+    let rest = text.slice(1).trim();
+    while (!rest.startsWith(']')) {
+      const parsed = parseValue(rest, lineNumber);
+      values.push(parsed[0]);
+      rest = parsed[1].trim();
+      if (rest.startsWith(',')) {
+        rest = rest.slice(1).trim();
+      } else if (!rest.startsWith(']')) {
+        throw new Error('line ' + lineNumber + ': expected , or ] in array');
+      }
+    }
+    return [values, rest.slice(1)];
+  }
+  throw new Error('line ' + lineNumber + ': unsupported value ' + text);
+}
+
+// Position of the = ending the key, skipping any inside quoted key parts
+function keyEnd(line) {
+  let quote = null;
+  for (let i = 0; i < line.length; i++) {
+    if (quote) {
+      if (line[i] == '\\' && quote == '"') i++;
+      else if (line[i] == quote) quote = null;
+    } else if (line[i] == '"' || line[i] == "'") {
+      quote = line[i];
+    } else if (line[i] == '=') {
+      return i;
+    }
+  }
+  return -1;
+}
+
+function tableAt(root, keys) {
+  let table = root;
+  keys.forEach((key) => {
+    if (table[key] === undefined) table[key] = {};
+    table = table[key];
+  });
+  return table;
+}
+
+// Parses the subset of TOML used by the config file: tables, dotted and quoted
+// keys, strings, numbers, booleans and single-line arrays
+function parseToml(text) {
+  const root = {};
+  let table = root;
+
+  text.split(/\r?\n/).forEach((rawLine, index) => {
+    const lineNumber = index + 1;
+    const line = rawLine.trim();
+    if (line == '' || line.startsWith('#')) return;
+
+    let match;
+    if ((match = line.match(/^\[([^\]]+)\]\s*(#.*)?$/))) {
+      table = tableAt(root, splitKey(match[1], lineNumber));
+      return;
+    }
+
+    const equals = keyEnd(line);
+    if (equals < 1) {
+      throw new Error('line ' + lineNumber + ': expected key = value');
+    }
+    const keys = splitKey(line.slice(0, equals).trim(), lineNumber);
+    const parsed = parseValue(line.slice(equals + 1).trim(), lineNumber);
+    if (parsed[1].trim() != '' && !parsed[1].trim().startsWith('#')) {
+      throw new Error('line ' + lineNumber + ': unexpected ' + parsed[1].trim());
+    }
+    tableAt(table, keys.slice(0, -1))[keys[keys.length - 1]] = parsed[0];
+  });
+
+  return root;
+}
+
+module.exports = {
+  CONFIG_FILENAME,
+  defaultConfigPaths,
+  defaultOutputDir,
+  loadConfig,
+  parseToml,
+};
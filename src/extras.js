@@ -0,0 +1,261 @@
+// Zone configuration beyond DNS records, each exported with its own
+// --include-* option to a file in the zone's directory, e.g.
+// example.com/settings.json
+const EXTRAS = {
+  settings: {
+    option: 'includeSettings',
+    file: 'settings.json',
+    fetch: (client, domain) =>
+      apiResult(client, 'zones/' + domain.id + '/settings'),
+  },
+  'page-rules': {
+    option: 'includePageRules',
+    file: 'pagerules.json',
+    fetch: (client, domain) =>
+      apiResult(client, 'zones/' + domain.id + '/pagerules'),
+  },
+  rulesets: {
+    option: 'includeRulesets',
+    file: 'rulesets.json',
+    fetch: (client, domain) =>
+      phaseRulesets(client, domain, [
+        'http_request_dynamic_redirect',
+        'http_request_transform',
+        'http_request_late_transform',
+        'http_response_headers_transform',
+        'http_request_cache_settings',
+        'http_request_origin',
+        'http_config_settings',
+        'http_response_compression',
+        'http_custom_errors',
+      ]),
+  },
+  // Managed rulesets are deployed and overridden from the zone's own
+  // http_request_firewall_managed ruleset
+  waf: {
+    option: 'includeWaf',
+    file: 'waf.json',
+    fetch: async (client, domain) => ({
+      custom: await phaseRulesets(client, domain, [
+        'http_request_firewall_custom',
+      ]),
+      managed: await phaseRulesets(client, domain, [
+        'http_request_firewall_managed',
+      ]),
+    }),
+  },
+  // Both the rulesets-based rules and the older rate limiting API
+  'rate-limits': {
+    option: 'includeRateLimits',
+    file: 'ratelimits.json',
+    fetch: async (client, domain) => ({
+      rulesets: await phaseRulesets(client, domain, ['http_ratelimit']),
+      legacy: await pagedResults(client, 'zones/' + domain.id + '/rate_limits'),
+    }),
+  },
+  // Custom domains belong to the account, filtered here to the zone
+  'workers-routes': {
+    option: 'includeWorkersRoutes',
+    file: 'workers.json',
+    fetch: async (client, domain) => ({
+      routes: await apiResult(client, 'zones/' + domain.id + '/workers/routes'),
+      custom_domains: (
+        await apiResult(
+          client,
+          'accounts/' + domain.accountId + '/workers/domains',
+          { zone_id: domain.id }
+        )
+      ).filter((binding) => binding.zone_id == domain.id),
+    }),
+  },
+  // The status, algorithm and DS record to give the registrar
+  dnssec: {
+    option: 'includeDnssec',
+    file: 'dnssec.json',
+    fetch: (client, domain) =>
+      apiResult(client, 'zones/' + domain.id + '/dnssec'),
+  },
+  // Assigned nameservers differ between accounts, and custom ones are set
+  // up on the zone itself
+  nameservers: {
+    option: 'includeNameservers',
+    file: 'nameservers.json',
+    fetch: async (client, domain) => {
+      const zone = await apiResult(client, 'zones/' + domain.id);
+      return {
+        name_servers: zone.name_servers,
+        vanity_name_servers: zone.vanity_name_servers
+          ? zone.vanity_name_servers
+          : [],
+        original_name_servers: zone.original_name_servers,
+        original_registrar: zone.original_registrar,
+      };
+    },
+  },
+  'email-routing': {
+    option: 'includeEmailRouting',
+    file: 'email-routing.json',
+    fetch: emailRouting,
+  },
+  // Certificate coverage: Universal SSL, every certificate pack, how each is
+  // validated and the SSL status of any custom hostnames
+  ssl: {
+    option: 'includeSsl',
+    file: 'ssl.json',
+    fetch: async (client, domain) => {
+      const base = 'zones/' + domain.id;
+      return {
+        universal: await apiResult(client, base + '/ssl/universal/settings'),
+        certificate_packs: await pagedResults(
+          client,
+          base + '/ssl/certificate_packs',
+          { status: 'all' }
+        ),
+        verification: await apiResult(client, base + '/ssl/verification'),
+        custom_hostnames: await pagedResults(
+          client,
+          base + '/custom_hostnames'
+        ),
+      };
+    },
+  },
+};
+
+async function apiResult(client, url, params) {
+  const response = await client.get(url, { params: params });
+  if (!response.data.success) {
+    throw new Error(
+      response.data.errors.map((error) => error.message).join(', ')
+    );
+  }
+  return response.data.result;
+}
+
+async function pagedResults(client, url, params) {
+  const results = [];
+  let page = 1;
+  let pageInfo;
+  do {
+    const response = await client.get(url, {
+      params: Object.assign({}, params, { page: page }),
+    });
+    if (!response.data.success) {
+      throw new Error(
+        response.data.errors.map((error) => error.message).join(', ')
+      );
+    }
+    pageInfo = response.data.result_info;
+    results.push(...response.data.result);
+    page = pageInfo.page + 1;
+  } while (pageInfo.page < pageInfo.total_pages);
+  return results;
+}
+
+// The zone's own rulesets in these phases, each with its rules. The
+// listing leaves the rules out, so each ruleset is fetched in full.
+async function phaseRulesets(client, domain, phases) {
+  const listed = await apiResult(client, 'zones/' + domain.id + '/rulesets');
+  const rulesets = [];
+  for (const ruleset of listed) {
+    if (ruleset.kind != 'zone' || !phases.includes(ruleset.phase)) continue;
+    rulesets.push(
+      await apiResult(
+        client,
+        'zones/' + domain.id + '/rulesets/' + ruleset.id
+      )
+    );
+  }
+  return rulesets;
+}
+
+// Destination addresses belong to the account, so only those the zone's
+// rules forward to are kept
+async function emailRouting(client, domain) {
+  const base = 'zones/' + domain.id + '/email/routing';
+  const rules = await pagedResults(client, base + '/rules');
+  const catchAll = await apiResult(client, base + '/rules/catch_all');
+  const forwarded = [];
+  rules.concat(catchAll).forEach((rule) => {
+    (rule.actions ? rule.actions : [])
+      .filter((action) => action.type == 'forward')
+      .forEach((action) => forwarded.push(...action.value));
+  });
+  const addresses = await pagedResults(
+    client,
+    'accounts/' + domain.accountId + '/email/routing/addresses'
+  );
+  return {
+    settings: await apiResult(client, base),
+    rules: rules,
+    catch_all: catchAll,
+    addresses: addresses.filter((address) =>
+      forwarded.includes(address.email)
+    ),
+  };
+}
+
+function selectedExtras(options) {
+  return Object.keys(EXTRAS).filter((name) => options[EXTRAS[name].option]);
+}
+
+// The extra files for a zone as [{ file, contents }], and the extras that
+// could not be fetched as [{ name, error }]. One failing, say for a missing
+// entitlement or token scope, leaves the others and the records alone.
+async function fetchExtras(client, domain, options) {
+  const files = [];
+  const failed = [];
+  for (const name of selectedExtras(options)) {
+    const extra = EXTRAS[name];
+    try {
+      const result = await extra.fetch(client, domain);
+      files.push({
+        file: extra.file,
+        contents: JSON.stringify(result, null, 2) + '\n',
+      });
+    } catch (error) {
+      failed.push({ name: name, error: error });
+    }
+  }
+  return { files: files, failed: failed };
+}
+
+// Each account the zones belong to, with how many zones and members it has
+// and how many of its zones were exported
+async function fetchAccounts(client, domains) {
+  const accounts = [];
+  for (const domain of domains) {
+    if (!domain.accountId) continue;
+    let account = accounts.find((found) => found.id == domain.accountId);
+    if (!account) {
+      const accountClient = domain.client ? domain.client : client;
+      const details = await apiResult(
+        accountClient,
+        'accounts/' + domain.accountId
+      );
+      // Only the total is needed, not the members themselves
+      const members = await accountClient.get(
+        'accounts/' + domain.accountId + '/members',
+        { params: { per_page: 1 } }
+      );
+      const zones = await accountClient.get('zones', {
+        params: { 'account.id': domain.accountId, per_page: 1 },
+      });
+      account = {
+        id: details.id,
+        name: details.name,
+        type: details.type,
+        created_on: details.created_on,
+        member_count: members.data.result_info.total_count,
+        zone_count: zones.data.result_info.total_count,
+        exported_zone_count: 0,
+        zones: [],
+      };
+      accounts.push(account);
+    }
+    account.exported_zone_count++;
+    account.zones.push(domain.name);
+  }
+  return accounts;
+}
+
+module.exports = { EXTRAS, fetchExtras, fetchAccounts };
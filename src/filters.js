@@ -0,0 +1,26 @@
+// Accept comma separated strings from flags or arrays from the config file
+function parseList(value) {
+  const items = Array.isArray(value) ? value : String(value).split(',');
+  return items.map((item) => String(item).trim()).filter((item) => item);
+}
+
+// Shell style glob where * matches any run of characters and ? a single one
+function globToRegExp(glob) {
+  const source = glob
+    .split('')
+    .map((char) =>
+      char == '*'
+        ? '.*'
+        : char == '?'
+        ? '.'
+        : char.replace(/[.+^${}()|[\]\\]/g, '\\$&')
+    )
+    .join('');
+  return new RegExp('^' + source + '$', 'i');
+}
+
+function matchesAny(name, patterns) {
+  return patterns.some((pattern) => globToRegExp(pattern).test(name));
+}
+
+module.exports = { parseList, globToRegExp, matchesAny };
@@ -0,0 +1,94 @@
+const { relativeName } = require('./records');
+const { toYaml } = require('./yaml');
+
+const MODULE = 'community.general.cloudflare_dns';
+
+// Types the cloudflare_dns module manages, and their type-specific arguments
+const RECORD_ARGUMENTS = {
+  A: (record) => ({ value: record.content }),
+  AAAA: (record) => ({ value: record.content }),
+  CNAME: (record) => ({ value: record.content }),
+  NS: (record) => ({ value: record.content }),
+  TXT: (record) => ({ value: record.content }),
+  SPF: (record) => ({ value: record.content }),
+  MX: (record) => ({ value: record.content, priority: record.priority }),
+  // The module builds the _service._proto prefix itself
+  SRV: (record, name) => {
+    const labels = name.split('.');
+    return {
+      record: labels.length > 2 ? labels.slice(2).join('.') : '@',
+      service: labels[0].replace(/^_/, ''),
+      proto: labels[1] ? labels[1].replace(/^_/, '') : 'tcp',
+      value: record.data.target,
+      priority: record.data.priority,
+      weight: record.data.weight,
+      port: record.data.port,
+    };
+  },
+  CAA: (record) => ({
+    flag: record.data.flags,
+    tag: record.data.tag,
+    value: record.data.value,
+  }),
+};
+
+function recordTask(record, zone) {
+  const name = relativeName(record, zone);
+  const args = Object.assign(
+    { zone: zone.name, record: name ? name : '@', type: record.type },
+    RECORD_ARGUMENTS[record.type](record, name)
+  );
+  args.ttl = record.ttl;
+  if (record.proxiable || record.proxied) args.proxied = record.proxied;
+  args.state = 'present';
+
+  const task = { name: record.type + ' ' + record.name };
+  task[MODULE] = args;
+  return task;
+}
+
+// A play for the zone, and the records it had to leave out
+function zonePlay(zone) {
+  const tasks = [];
+  const skipped = [];
+  zone.records.forEach((record) => {
+    // The apex NS records belong to Cloudflare
+    if (record.type == 'NS' && record.name == zone.name) return;
+    if (RECORD_ARGUMENTS[record.type]) {
+      tasks.push(recordTask(record, zone));
+    } else {
+      skipped.push(record.type + ' ' + record.name);
+    }
+  });
+
+  const defaults = {};
+  defaults[MODULE] = {
+    api_token: "{{ lookup('env', 'CLOUDFLARE_API_TOKEN') }}",
+  };
+  const play = {
+    name: 'DNS records for ' + zone.name,
+    hosts: 'localhost',
+    gather_facts: false,
+    module_defaults: defaults,
+    tasks: tasks,
+  };
+  return { play: play, skipped: skipped };
+}
+
+module.exports = {
+  extension: 'yml',
+  comment: '#',
+  source: 'records',
+  render(zone) {
+    return this.renderAll([zone]);
+  },
+  renderAll(zones) {
+    const plays = zones.map(zonePlay);
+    const header = []
+      .concat(...plays.map((play) => play.skipped))
+      .map((record) => '# Not supported by ' + MODULE + ', skipped: ' + record)
+      .map((line) => line + '\n')
+      .join('');
+    return '---\n' + header + toYaml(plays.map((play) => play.play));
+  },
+};
@@ -0,0 +1,200 @@
+const { relativeName } = require('./records');
+
+const API_VERSION = '2018-05-01';
+
+// Cloudflare's automatic TTL has no Azure DNS equivalent
+const AUTO_TTL = 1;
+const DEFAULT_TTL = 300;
+
+// Azure DNS holds at most 255 characters in each string of a TXT value
+const MAX_STRING = 255;
+
+function txtStrings(text) {
+  const strings = [];
+  for (let i = 0; i < text.length || i == 0; i += MAX_STRING) {
+    strings.push(text.slice(i, i + MAX_STRING));
+  }
+  return strings;
+}
+
+// Record set properties for each type Azure DNS supports
+const RECORD_TYPES = {
+  A: (records) => ({
+    ARecords: records.map((record) => ({ ipv4Address: record.content })),
+  }),
+  AAAA: (records) => ({
+    AAAARecords: records.map((record) => ({ ipv6Address: record.content })),
+  }),
+  CNAME: (records) => ({ CNAMERecord: { cname: records[0].content } }),
+  MX: (records) => ({
+    MXRecords: records.map((record) => ({
+      preference: record.priority,
+      exchange: record.content,
+    })),
+  }),
+  NS: (records) => ({
+    NSRecords: records.map((record) => ({ nsdname: record.content })),
+  }),
+  PTR: (records) => ({
+    PTRRecords: records.map((record) => ({ ptrdname: record.content })),
+  }),
+  SRV: (records) => ({
+    SRVRecords: records.map((record) => ({
+      priority: record.data.priority,
+      weight: record.data.weight,
+      port: record.data.port,
+      target: record.data.target,
+    })),
+  }),
+  TXT: (records) => ({
+    TXTRecords: records.map((record) => ({
+      value: txtStrings(record.content),
+    })),
+  }),
+  CAA: (records) => ({
+    caaRecords: records.map((record) => ({
+      flags: record.data.flags,
+      tag: record.data.tag,
+      value: record.data.value,
+    })),
+  }),
+};
+
+// Escape interpolation so values are taken literally
+function bicepString(value) {
+  return (
+    "'" +
+    String(value)
+      .replace(/\\/g, '\\\\')
+      .replace(/'/g, "\\'")
+      .replace(/\$\{/g, '\\${') +
+    "'"
+  );
+}
+
+// A reference to another resource, written as its symbolic name
+function Reference(name) {
+  this.name = name;
+}
+
+function bicepValue(value, indent) {
+  indent = indent ? indent : '';
+  const inner = indent + '  ';
+  if (value instanceof Reference) {
+    return value.name;
+  } else if (Array.isArray(value)) {
+    return (
+      '[\n' +
+      value.map((item) => inner + bicepValue(item, inner) + '\n').join('') +
+      indent +
+      ']'
+    );
+  } else if (value && typeof value == 'object') {
+    return (
+      '{\n' +
+      Object.keys(value)
+        .map((key) => inner + key + ': ' + bicepValue(value[key], inner))
+        .map((line) => line + '\n')
+        .join('') +
+      indent +
+      '}'
+    );
+  } else if (typeof value == 'string') {
+    return bicepString(value);
+  }
+  return String(value);
+}
+
+// Bicep identifiers from the name and type, e.g. www_example_com_cname
+function symbolicName(base, used) {
+  const cleaned = base.toLowerCase().replace(/[^a-z0-9_]/g, '_');
+  const start = /^[a-z_]/.test(cleaned) ? cleaned : '_' + cleaned;
+  let name = start;
+  for (let i = 2; used.has(name); i++) {
+    name = start + '_' + i;
+  }
+  used.add(name);
+  return name;
+}
+
+// Azure makes its own apex NS records, and has no CNAME flattening
+function supported(record, zone) {
+  const apex = relativeName(record, zone) == '';
+  if (apex && ['NS', 'CNAME'].includes(record.type)) return false;
+  return Boolean(RECORD_TYPES[record.type]);
+}
+
+function resource(name, type, body) {
+  return (
+    'resource ' +
+    name +
+    " '" +
+    type +
+    '@' +
+    API_VERSION +
+    "' = " +
+    bicepValue(body) +
+    '\n'
+  );
+}
+
+// Symbolic names are shared across zones rendered with the same used set
+function renderZone(zone, used) {
+  const sets = new Map();
+  const skipped = [];
+  zone.records.forEach((record) => {
+    if (!supported(record, zone)) {
+      if (record.type != 'NS') skipped.push(record.type + ' ' + record.name);
+      return;
+    }
+    const key = record.name + ' ' + record.type;
+    if (!sets.has(key)) sets.set(key, []);
+    sets.get(key).push(record);
+  });
+
+  const zoneName = symbolicName('zone_' + zone.name, used);
+  const blocks = [
+    resource(zoneName, 'Microsoft.Network/dnsZones', {
+      name: zone.name,
+      location: 'global',
+    }),
+  ];
+  sets.forEach((records) => {
+    const first = records[0];
+    const name = relativeName(first, zone);
+    const properties = Object.assign(
+      { TTL: first.ttl == AUTO_TTL ? DEFAULT_TTL : first.ttl },
+      RECORD_TYPES[first.type](records)
+    );
+    blocks.push(
+      resource(
+        symbolicName(first.name + '_' + first.type, used),
+        'Microsoft.Network/dnsZones/' + first.type,
+        {
+          parent: new Reference(zoneName),
+          name: name ? name : '@',
+          properties: properties,
+        }
+      )
+    );
+  });
+  const header = skipped
+    .map((record) => '// Not supported by Azure DNS, skipped: ' + record)
+    .map((line) => line + '\n')
+    .join('');
+  return header + blocks.join('\n');
+}
+
+// A dnsZones resource per zone with a child resource per record set
+module.exports = {
+  extension: 'bicep',
+  comment: '//',
+  source: 'records',
+  render(zone) {
+    return renderZone(zone, new Set());
+  },
+  renderAll(zones) {
+    const used = new Set();
+    return zones.map((zone) => renderZone(zone, used)).join('\n');
+  },
+};
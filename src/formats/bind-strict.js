@@ -0,0 +1,102 @@
+const { parseZone, tokenize, recordLine } = require('../zonefile');
+
+// Directives every strict parser understands; $INCLUDE cannot be resolved
+// from an export
+const DIRECTIVES = ['$ORIGIN', '$TTL'];
+
+// The longest character-string a TXT record can hold
+const MAX_STRING = 255;
+
+function unescape(text) {
+  return text.replace(/\\(.)/g, '$1');
+}
+
+function quote(text) {
+  return '"' + text.replace(/(["\\])/g, '\\$1') + '"';
+}
+
+// TXT data with any string over 255 characters split into several
+function splitStrings(data) {
+  return data.replace(/"((?:[^"\\]|\\.)*)"/g, (match, body) => {
+    const text = unescape(body);
+    if (text.length <= MAX_STRING) return match;
+    const parts = [];
+    for (let i = 0; i < text.length; i += MAX_STRING) {
+      parts.push(quote(text.slice(i, i + MAX_STRING)));
+    }
+    return parts.join(' ');
+  });
+}
+
+function inZone(name, origin) {
+  return name == origin || name.endsWith('.' + origin);
+}
+
+// Problems a strict parser would reject and that cannot be adjusted away
+function zoneProblems(text, parsed) {
+  const problems = [];
+  tokenize(text).forEach((line) => {
+    const first = line.tokens[0];
+    if (first.startsWith('$') && !DIRECTIVES.includes(first)) {
+      problems.push('unsupported directive ' + first);
+    }
+  });
+
+  const soa = parsed.records.filter((record) => record.type == 'SOA');
+  if (soa.length != 1) {
+    problems.push('expected one SOA record, found ' + soa.length);
+  } else if (soa[0].name != parsed.origin) {
+    problems.push('SOA record is not at the zone apex');
+  }
+
+  const types = {};
+  parsed.records.forEach((record) => {
+    if (!inZone(record.name, parsed.origin)) {
+      problems.push(record.name + ' is outside the zone');
+    }
+    types[record.name] = (types[record.name] || []).concat(record.type);
+  });
+  Object.keys(types).forEach((name) => {
+    if (types[name].includes('CNAME') && types[name].length > 1) {
+      problems.push(name + ' has a CNAME alongside other records');
+    }
+  });
+  return problems;
+}
+
+// The BIND export checked and adjusted for knotc and nsd-checkzone: an
+// explicit TTL and class on every record, fully qualified names and TXT
+// strings within the length limit
+module.exports = {
+  extension: 'zone',
+  comment: ';',
+  render(zone) {
+    const parsed = parseZone(zone.text, zone.name);
+    const problems = zoneProblems(zone.text, parsed);
+    if (problems.length) {
+      throw new Error('Not a valid strict zone: ' + problems.join(', '));
+    }
+
+    // Records without a TTL fall back to $TTL, then the SOA minimum
+    const soa = parsed.records.find((record) => record.type == 'SOA');
+    const minimum = Number(soa.data.split(/\s+/)[6]);
+    const defaultTtl = parsed.ttl !== null ? parsed.ttl : minimum;
+    const lines = parsed.records.map((record) =>
+      recordLine(
+        Object.assign({}, record, {
+          ttl: record.ttl !== null ? record.ttl : defaultTtl,
+          data:
+            record.type == 'TXT' || record.type == 'SPF'
+              ? splitStrings(record.data)
+              : record.data,
+        })
+      )
+    );
+
+    return (
+      ['$ORIGIN ' + parsed.origin + '.', '$TTL ' + defaultTtl]
+        .concat(lines)
+        .join('\n') + '\n'
+    );
+  },
+};
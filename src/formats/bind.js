@@ -0,0 +1,8 @@
+// The zone file exactly as Cloudflare's export endpoint returns it
+module.exports = {
+  extension: 'txt',
+  comment: ';',
+  render(zone) {
+    return zone.text;
+  },
+};
@@ -0,0 +1,21 @@
+// Zone files for CoreDNS's file plugin, with a Corefile serving each of them
+// from the directory they are written to
+module.exports = {
+  extension: 'db',
+  comment: ';',
+  indexFile: 'Corefile',
+  render(zone) {
+    return zone.text;
+  },
+  index(zones) {
+    return zones
+      .map(
+        (zone) =>
+          zone.name +
+          ' {\n    file ' +
+          zone.name +
+          '.db\n    errors\n    log\n}\n'
+      )
+      .join('\n');
+  },
+};
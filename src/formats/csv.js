@@ -0,0 +1,47 @@
+const COLUMNS = [
+  'zone',
+  'type',
+  'name',
+  'content',
+  'ttl',
+  'priority',
+  'proxied',
+  // The record's own ID, for tools that update records in place
+  'id',
+];
+
+function csvField(value) {
+  const text = value === undefined || value === null ? '' : String(value);
+  return /[",\r\n]/.test(text) ? '"' + text.replace(/"/g, '""') + '"' : text;
+}
+
+function csvRows(rows) {
+  return rows.map((row) => row.map(csvField).join(',') + '\r\n').join('');
+}
+
+module.exports = {
+  extension: 'csv',
+  source: 'records',
+  csvRows,
+  render(zone) {
+    return this.renderAll([zone]);
+  },
+  renderAll(zones) {
+    const rows = [COLUMNS];
+    zones.forEach((zone) => {
+      zone.records.forEach((record) => {
+        rows.push([
+          zone.name,
+          record.type,
+          record.name,
+          record.content,
+          record.ttl,
+          record.priority,
+          record.proxied,
+          record.id,
+        ]);
+      });
+    });
+    return csvRows(rows);
+  },
+};
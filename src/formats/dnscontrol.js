@@ -0,0 +1,76 @@
+const { relativeName, fqdn } = require('./records');
+
+// dnscontrol's default TTL, left out of the generated calls
+const DEFAULT_TTL = 300;
+
+// Arguments after the name for each record type dnscontrol supports
+const RECORD_ARGUMENTS = {
+  A: (record) => [record.content],
+  AAAA: (record) => [record.content],
+  CNAME: (record) => [fqdn(record.content)],
+  NS: (record) => [fqdn(record.content)],
+  PTR: (record) => [fqdn(record.content)],
+  TXT: (record) => [record.content],
+  MX: (record) => [record.priority, fqdn(record.content)],
+  SRV: (record) => [
+    record.data.priority,
+    record.data.weight,
+    record.data.port,
+    fqdn(record.data.target),
+  ],
+  CAA: (record) => [record.data.tag, record.data.value],
+};
+
+function jsValue(value) {
+  return typeof value == 'number' ? String(value) : JSON.stringify(value);
+}
+
+function recordCall(record, zone) {
+  const name = relativeName(record, zone);
+  const args = [name ? name : '@']
+    .concat(RECORD_ARGUMENTS[record.type](record))
+    .map(jsValue);
+  if (record.type == 'CAA' && record.data.flags & 128) {
+    args.push('CAA_CRITICAL');
+  }
+  if (record.ttl != DEFAULT_TTL) args.push('TTL(' + record.ttl + ')');
+  if (record.proxied) args.push('CF_PROXY_ON');
+  return record.type + '(' + args.join(', ') + ')';
+}
+
+module.exports = {
+  extension: 'js',
+  comment: '//',
+  source: 'records',
+  render(zone) {
+    const lines = [];
+    const skipped = [];
+    zone.records.forEach((record) => {
+      // The apex NS records belong to Cloudflare
+      if (record.type == 'NS' && record.name == zone.name) return;
+      if (RECORD_ARGUMENTS[record.type]) {
+        lines.push(recordCall(record, zone));
+      } else {
+        skipped.push(record.type + ' ' + record.name);
+      }
+    });
+
+    return (
+      '// dnsconfig.js fragment for ' +
+      zone.name +
+      '. It expects REG_NONE and DSP_CLOUDFLARE\n' +
+      '// to be defined, for example:\n' +
+      '// var REG_NONE = NewRegistrar("none");\n' +
+      '// var DSP_CLOUDFLARE = NewDnsProvider("cloudflare");\n' +
+      skipped
+        .map((record) => '// Not supported by dnscontrol, skipped: ' + record)
+        .map((line) => line + '\n')
+        .join('') +
+      'D(' +
+      JSON.stringify(zone.name) +
+      ', REG_NONE, DnsProvider(DSP_CLOUDFLARE),\n' +
+      lines.map((line) => '  ' + line + ',\n').join('') +
+      ');\n'
+    );
+  },
+};
@@ -0,0 +1,78 @@
+const { relativeName } = require('./records');
+const { toYaml } = require('./yaml');
+
+// Types external-dns can manage, and how each target is written
+const TARGET_TYPES = {
+  A: (record) => record.content,
+  AAAA: (record) => record.content,
+  CNAME: (record) => record.content,
+  NS: (record) => record.content,
+  PTR: (record) => record.content,
+  TXT: (record) => record.content,
+  MX: (record) => record.priority + ' ' + record.content,
+  SRV: (record) =>
+    [
+      record.data.priority,
+      record.data.weight,
+      record.data.port,
+      record.data.target,
+    ].join(' '),
+};
+
+// Cloudflare's automatic TTL, left out so external-dns uses its default
+const AUTO_TTL = 1;
+
+const PROXIED = 'external-dns.alpha.kubernetes.io/cloudflare-proxied';
+
+// Kubernetes object names are lowercase letters, digits and dashes
+function resourceName(zone) {
+  return zone.name.toLowerCase().replace(/[^a-z0-9-]+/g, '-');
+}
+
+// One endpoint per name and type, with every record's target
+function endpoints(zone, skipped) {
+  const sets = new Map();
+  zone.records.forEach((record) => {
+    // The apex NS records belong to Cloudflare
+    if (record.type == 'NS' && relativeName(record, zone) == '') return;
+    if (!TARGET_TYPES[record.type]) {
+      skipped.push(record.type + ' ' + record.name);
+      return;
+    }
+    const key = record.name + ' ' + record.type;
+    if (!sets.has(key)) sets.set(key, []);
+    sets.get(key).push(record);
+  });
+  return Array.from(sets.values()).map((records) => {
+    const first = records[0];
+    const endpoint = { dnsName: first.name };
+    if (first.ttl != AUTO_TTL) endpoint.recordTTL = first.ttl;
+    endpoint.recordType = first.type;
+    endpoint.targets = records.map(TARGET_TYPES[first.type]);
+    if (first.proxied) {
+      endpoint.providerSpecific = [{ name: PROXIED, value: 'true' }];
+    }
+    return endpoint;
+  });
+}
+
+// A DNSEndpoint manifest per zone, for external-dns's crd source
+module.exports = {
+  extension: 'yaml',
+  comment: '#',
+  source: 'records',
+  render(zone) {
+    const skipped = [];
+    const manifest = {
+      apiVersion: 'externaldns.k8s.io/v1alpha1',
+      kind: 'DNSEndpoint',
+      metadata: { name: resourceName(zone) },
+      spec: { endpoints: endpoints(zone, skipped) },
+    };
+    const header = skipped
+      .map((record) => '# Not supported by external-dns, skipped: ' + record)
+      .map((line) => line + '\n')
+      .join('');
+    return '---\n' + header + toYaml(manifest);
+  },
+};
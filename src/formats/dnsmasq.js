@@ -0,0 +1,49 @@
+function txtValue(content) {
+  return /^".*"$/.test(content)
+    ? content
+    : '"' + content.replace(/(["\\])/g, '\\$1') + '"';
+}
+
+// dnsmasq options for each type it can answer for itself. address= also
+// answers for every name below the one given.
+const OPTION_TYPES = {
+  A: (record) => 'address=/' + record.name + '/' + record.content,
+  AAAA: (record) => 'address=/' + record.name + '/' + record.content,
+  CNAME: (record) => 'cname=' + record.name + ',' + record.content,
+  TXT: (record) => 'txt-record=' + record.name + ',' + txtValue(record.content),
+  MX: (record) =>
+    'mx-host=' + record.name + ',' + record.content + ',' + record.priority,
+  SRV: (record) =>
+    'srv-host=' +
+    [
+      record.name,
+      record.data.target,
+      record.data.port,
+      record.data.priority,
+      record.data.weight,
+    ].join(','),
+  PTR: (record) => 'ptr-record=' + record.name + ',' + record.content,
+};
+
+// A dnsmasq.d fragment answering for the zone's names
+module.exports = {
+  extension: 'conf',
+  comment: '#',
+  source: 'records',
+  render(zone) {
+    const lines = ['# ' + zone.name];
+    zone.records.forEach((record) => {
+      if (OPTION_TYPES[record.type]) {
+        lines.push(OPTION_TYPES[record.type](record));
+      } else if (record.type != 'NS') {
+        lines.push(
+          '# Not supported by dnsmasq, skipped: ' +
+            record.type +
+            ' ' +
+            record.name
+        );
+      }
+    });
+    return lines.join('\n') + '\n';
+  },
+};
@@ -0,0 +1,54 @@
+const { relativeName, fqdn } = require('./records');
+const { VALUE_TYPES } = require('./route53');
+const { toYaml } = require('./yaml');
+
+// Cloudflare's automatic TTL has no Cloud DNS equivalent
+const AUTO_TTL = 1;
+const DEFAULT_TTL = 300;
+
+// Cloud DNS makes its own apex NS records, and like Route 53 takes the
+// rdata in zone file form. Apex CNAMEs only work on Cloudflare.
+function supported(record, zone) {
+  const apex = relativeName(record, zone) == '';
+  if (apex && ['NS', 'CNAME'].includes(record.type)) return false;
+  return Boolean(VALUE_TYPES[record.type]);
+}
+
+function recordSets(zone, skipped) {
+  const sets = new Map();
+  zone.records.forEach((record) => {
+    if (!supported(record, zone)) {
+      if (record.type != 'NS') skipped.push(record.type + ' ' + record.name);
+      return;
+    }
+    const key = record.name + ' ' + record.type;
+    if (!sets.has(key)) sets.set(key, []);
+    sets.get(key).push(record);
+  });
+  return Array.from(sets.values()).map((records) => {
+    const first = records[0];
+    return {
+      kind: 'dns#resourceRecordSet',
+      name: fqdn(first.name),
+      rrdatas: records.map(VALUE_TYPES[first.type]),
+      ttl: first.ttl == AUTO_TTL ? DEFAULT_TTL : first.ttl,
+      type: first.type,
+    };
+  });
+}
+
+// The YAML of gcloud dns record-sets export, a document per record set
+module.exports = {
+  extension: 'yaml',
+  comment: '#',
+  source: 'records',
+  render(zone) {
+    const skipped = [];
+    const sets = recordSets(zone, skipped);
+    const header = skipped
+      .map((record) => '# Not supported by Cloud DNS, skipped: ' + record)
+      .map((line) => line + '\n')
+      .join('');
+    return header + sets.map((set) => '---\n' + toYaml(set)).join('');
+  },
+};
@@ -0,0 +1,37 @@
+// Only names at or below the --hosts-subdomain are written
+function inSubdomain(record, zone, subdomain) {
+  if (!subdomain) return true;
+  const base = subdomain.replace(/\.$/, '');
+  // Names outside the zone are taken as relative to it
+  const suffix =
+    base == zone.name || base.endsWith('.' + zone.name)
+      ? base
+      : base + '.' + zone.name;
+  return record.name == suffix || record.name.endsWith('.' + suffix);
+}
+
+// An /etc/hosts fragment of the zone's A and AAAA records, a line per
+// address with each name that resolves to it. Wildcards cannot be listed.
+module.exports = {
+  extension: 'hosts',
+  comment: '#',
+  source: 'records',
+  render(zone, options) {
+    const subdomain = options ? options.hostsSubdomain : null;
+    const addresses = new Map();
+    zone.records
+      .filter((record) => record.type == 'A' || record.type == 'AAAA')
+      .filter((record) => !record.name.startsWith('*.'))
+      .filter((record) => inSubdomain(record, zone, subdomain))
+      .forEach((record) => {
+        if (!addresses.has(record.content)) addresses.set(record.content, []);
+        const names = addresses.get(record.content);
+        if (!names.includes(record.name)) names.push(record.name);
+      });
+    const lines = ['# ' + zone.name];
+    addresses.forEach((names, address) => {
+      lines.push(address + '\t' + names.join(' '));
+    });
+    return lines.join('\n') + '\n';
+  },
+};
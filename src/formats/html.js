@@ -0,0 +1,145 @@
+const { recordGroups, recordColumns, zoneFindings } = require('./report');
+
+const STYLE = [
+  'body { font-family: sans-serif; margin: 2em; color: #222; }',
+  'table { border-collapse: collapse; margin-bottom: 1.5em; }',
+  'th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }',
+  'th { background: #f3f3f3; }',
+  'td { font-family: monospace; word-break: break-all; }',
+].join('\n');
+
+function escapeHtml(text) {
+  return String(text)
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;');
+}
+
+function page(title, body) {
+  return (
+    '<!DOCTYPE html>\n<html lang="en">\n<head>\n<meta charset="utf-8">\n' +
+    '<title>' +
+    escapeHtml(title) +
+    '</title>\n<style>\n' +
+    STYLE +
+    '\n</style>\n</head>\n<body>\n' +
+    body +
+    '</body>\n</html>\n'
+  );
+}
+
+// Rows are already escaped HTML
+function table(headers, rows) {
+  return (
+    '<table>\n<tr>' +
+    headers.map((header) => '<th>' + escapeHtml(header) + '</th>').join('') +
+    '</tr>\n' +
+    rows
+      .map((row) => row.map((cell) => '<td>' + cell + '</td>').join(''))
+      .map((cells) => '<tr>' + cells + '</tr>\n')
+      .join('') +
+    '</table>\n'
+  );
+}
+
+// The most recent modified_on of the zone's records
+function lastChange(zone) {
+  const times = zone.records
+    .map((record) => record.modified_on)
+    .filter((time) => time)
+    .sort();
+  return times.length ? times[times.length - 1] : null;
+}
+
+function zoneBody(zone, heading) {
+  const groups = recordGroups(zone);
+  const findings = zoneFindings(zone);
+  const parts = [
+    heading,
+    '<p>Zone ID <code>' +
+      escapeHtml(zone.id) +
+      '</code>' +
+      (zone.account ? ' in the ' + escapeHtml(zone.account) + ' account' : '') +
+      ', ' +
+      zone.records.length +
+      ' records.</p>\n',
+    '<h2>Findings</h2>\n',
+    findings.length
+      ? '<ul>\n' +
+        findings
+          .map((finding) => '<li>' + escapeHtml(finding) + '</li>\n')
+          .join('') +
+        '</ul>\n'
+      : '<p>Nothing notable.</p>\n',
+  ];
+  groups.forEach((group) => {
+    const columns = recordColumns(group.records);
+    parts.push('<h2>' + escapeHtml(group.type) + '</h2>\n');
+    parts.push(
+      table(
+        columns.map((column) => column.title),
+        group.records.map((record) =>
+          columns.map((column) => escapeHtml(column.value(record)))
+        )
+      )
+    );
+  });
+  return parts.join('');
+}
+
+function indexTable(zones, link) {
+  return table(
+    ['Zone', 'Records', 'Types', 'Last change'],
+    zones.map((zone) => [
+      '<a href="' + link(zone) + '">' + escapeHtml(zone.name) + '</a>',
+      String(zone.records.length),
+      escapeHtml(
+        recordGroups(zone)
+          .map((group) => group.type)
+          .join(', ')
+      ),
+      escapeHtml(lastChange(zone) ? lastChange(zone) : '-'),
+    ])
+  );
+}
+
+// A page per zone, plus an index.html linking them all, written once every
+// zone has been exported. With --single-file everything is on one page.
+module.exports = {
+  extension: 'html',
+  source: 'records',
+  render(zone) {
+    return page(
+      zone.name,
+      '<p><a href="index.html">All zones</a></p>\n' +
+        zoneBody(zone, '<h1>' + escapeHtml(zone.name) + '</h1>\n')
+    );
+  },
+  index(zones) {
+    return page(
+      'DNS zones',
+      '<h1>DNS zones</h1>\n' +
+        indexTable(zones, (zone) => encodeURIComponent(zone.name) + '.html')
+    );
+  },
+  renderAll(zones) {
+    return page(
+      'DNS zones',
+      '<h1>DNS zones</h1>\n' +
+        indexTable(zones, (zone) => '#' + encodeURIComponent(zone.name)) +
+        zones
+          .map((zone) =>
+            zoneBody(
+              zone,
+              '<h1 id="' +
+                escapeHtml(encodeURIComponent(zone.name)) +
+                '">' +
+                escapeHtml(zone.name) +
+                '</h1>\n'
+            )
+          )
+          .join('')
+    );
+  },
+};
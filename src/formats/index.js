@@ -0,0 +1,48 @@
+// Output formats for exported zones. Each renders a zone
+// ({ id, name, text, records }) to the contents of one file, given the run's
+// render options: exportedAt, the time the run started, and hostsSubdomain
+// from --hosts-subdomain. Formats with source 'records' get the dns_records
+// API objects, with all of Cloudflare's metadata, and no text; the others get
+// the BIND export and its parsed records. Formats may define
+// renderAll(zones, options) for --single-file, otherwise the zones are
+// rendered one after another. comment is the line comment marker, for formats
+// that have one. combined formats always write every zone into one file, as
+// with --single-file. Formats with index(zones) also write an index of every
+// zone, named indexFile or index.<extension>, when writing to a directory or
+// archive.
+const FORMATS = {
+  bind: require('./bind'),
+  json: require('./json'),
+  yaml: require('./yaml'),
+  csv: require('./csv'),
+  terraform: require('./terraform'),
+  octodns: require('./octodns'),
+  dnscontrol: require('./dnscontrol'),
+  ansible: require('./ansible'),
+  'pulumi-ts': require('./pulumi'),
+  ndjson: require('./ndjson'),
+  sqlite: require('./sqlite'),
+  powerdns: require('./powerdns'),
+  'bind-strict': require('./bind-strict'),
+  markdown: require('./markdown'),
+  html: require('./html'),
+  dnsendpoint: require('./dnsendpoint'),
+  route53: require('./route53'),
+  'gcloud-dns': require('./gcloud-dns'),
+  'azure-bicep': require('./azure-bicep'),
+  hosts: require('./hosts'),
+  dnsmasq: require('./dnsmasq'),
+  unbound: require('./unbound'),
+  coredns: require('./coredns'),
+  template: require('./template'),
+};
+
+function renderZones(format, zones, options) {
+  if (format.renderAll) return format.renderAll(zones, options);
+  return zones
+    .map((zone) => format.render(zone, options))
+    .map((contents) => (contents.endsWith('\n') ? contents : contents + '\n'))
+    .join('\n');
+}
+
+module.exports = { FORMATS, FORMAT_NAMES: Object.keys(FORMATS), renderZones };
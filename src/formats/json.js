@@ -0,0 +1,12 @@
+const { zoneDocument } = require('./records');
+
+module.exports = {
+  extension: 'json',
+  source: 'records',
+  render(zone) {
+    return JSON.stringify(zoneDocument(zone), null, 2) + '\n';
+  },
+  renderAll(zones) {
+    return JSON.stringify(zones.map(zoneDocument), null, 2) + '\n';
+  },
+};
@@ -0,0 +1,58 @@
+const { recordGroups, recordColumns, zoneFindings } = require('./report');
+
+// Pipes would end a table cell, and newlines the row
+function cell(text) {
+  return text.replace(/\|/g, '\\|').replace(/\r?\n/g, ' ');
+}
+
+function table(headers, rows) {
+  return (
+    '| ' +
+    headers.map(cell).join(' | ') +
+    ' |\n| ' +
+    headers.map(() => '---').join(' | ') +
+    ' |\n' +
+    rows.map((row) => '| ' + row.map(cell).join(' | ') + ' |\n').join('')
+  );
+}
+
+// A readable report of the zone, for a wiki page or handover document
+module.exports = {
+  extension: 'md',
+  source: 'records',
+  render(zone) {
+    const groups = recordGroups(zone);
+    const findings = zoneFindings(zone);
+    const sections = [
+      '# ' + zone.name + '\n',
+      'Zone ID `' +
+        zone.id +
+        '`' +
+        (zone.account ? ' in the ' + zone.account + ' account' : '') +
+        ', ' +
+        zone.records.length +
+        ' records.\n',
+      table(
+        ['Type', 'Records'],
+        groups.map((group) => [group.type, String(group.records.length)])
+      ),
+      '## Findings\n',
+      findings.length
+        ? findings.map((finding) => '- ' + finding + '\n').join('')
+        : 'Nothing notable.\n',
+    ];
+    groups.forEach((group) => {
+      const columns = recordColumns(group.records);
+      sections.push('## ' + group.type + '\n');
+      sections.push(
+        table(
+          columns.map((column) => column.title),
+          group.records.map((record) =>
+            columns.map((column) => column.value(record))
+          )
+        )
+      );
+    });
+    return sections.join('\n');
+  },
+};
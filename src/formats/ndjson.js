@@ -0,0 +1,18 @@
+const { recordObject } = require('./records');
+
+// One JSON record per line, each naming its zone so lines from many zones
+// can be mixed, as with --single-file
+module.exports = {
+  extension: 'ndjson',
+  source: 'records',
+  render(zone) {
+    const fields = { zone: zone.name, zone_id: zone.id };
+    return zone.records
+      .map((record) => Object.assign({}, fields, recordObject(record)))
+      .map((record) => JSON.stringify(record) + '\n')
+      .join('');
+  },
+  renderAll(zones) {
+    return zones.map((zone) => this.render(zone)).join('');
+  },
+};
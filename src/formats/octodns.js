@@ -0,0 +1,98 @@
+const { relativeName, fqdn } = require('./records');
+const { toYaml } = require('./yaml');
+
+// Types octoDNS can manage on Cloudflare, and how each value is written
+const VALUE_TYPES = {
+  A: (record) => record.content,
+  AAAA: (record) => record.content,
+  CNAME: (record) => fqdn(record.content),
+  NS: (record) => fqdn(record.content),
+  PTR: (record) => fqdn(record.content),
+  TXT: (record) => record.content.replace(/;/g, '\\;'),
+  SPF: (record) => record.content.replace(/;/g, '\\;'),
+  MX: (record) => ({
+    preference: record.priority,
+    exchange: fqdn(record.content),
+  }),
+  SRV: (record) => ({
+    priority: record.data.priority,
+    weight: record.data.weight,
+    port: record.data.port,
+    target: fqdn(record.data.target),
+  }),
+  CAA: (record) => ({
+    flags: record.data.flags,
+    tag: record.data.tag,
+    value: record.data.value,
+  }),
+};
+
+// Types that take a single value rather than a list
+const SINGLE_VALUE_TYPES = ['CNAME', 'PTR'];
+
+// Cloudflare's automatic TTL, which octoDNS models as a provider option
+const AUTO_TTL = 1;
+
+// Group records into octoDNS record sets, one per name and type
+function recordSets(zone) {
+  const sets = new Map();
+  zone.records.forEach((record) => {
+    const key = record.name + ' ' + record.type;
+    if (!sets.has(key)) sets.set(key, []);
+    sets.get(key).push(record);
+  });
+  return Array.from(sets.values());
+}
+
+function recordSet(records) {
+  const first = records[0];
+  const set = {
+    type: first.type,
+    ttl: first.ttl == AUTO_TTL ? 300 : first.ttl,
+  };
+  const values = records.map(VALUE_TYPES[first.type]);
+  if (SINGLE_VALUE_TYPES.includes(first.type)) {
+    set.value = values[0];
+  } else {
+    set.values = values;
+  }
+
+  const cloudflare = {};
+  if (first.proxied) cloudflare.proxied = true;
+  if (first.ttl == AUTO_TTL) cloudflare['auto-ttl'] = true;
+  if (Object.keys(cloudflare).length) set.octodns = { cloudflare: cloudflare };
+  return set;
+}
+
+module.exports = {
+  extension: 'yaml',
+  comment: '#',
+  source: 'records',
+  render(zone) {
+    const document = {};
+    const skipped = [];
+    recordSets(zone).forEach((records) => {
+      const record = records[0];
+      const name = relativeName(record, zone);
+      // The apex NS records belong to Cloudflare
+      if (record.type == 'NS' && name == '') return;
+      if (!VALUE_TYPES[record.type]) {
+        skipped.push(record.type + ' ' + record.name);
+        return;
+      }
+      if (!document[name]) document[name] = [];
+      document[name].push(recordSet(records));
+    });
+
+    const names = Object.keys(document).sort();
+    const sorted = {};
+    names.forEach((name) => {
+      sorted[name] =
+        document[name].length == 1 ? document[name][0] : document[name];
+    });
+    const header = skipped
+      .map((record) => '# Not supported by octoDNS, skipped: ' + record + '\n')
+      .join('');
+    return '---\n' + header + toYaml(sorted);
+  },
+};
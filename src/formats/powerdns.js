@@ -0,0 +1,49 @@
+const { parseZone, recordLine } = require('../zonefile');
+
+// What Cloudflare's automatic TTL of 1 amounts to in practice
+const AUTOMATIC_TTL = 300;
+
+function qualifyName(name, origin) {
+  if (name.endsWith('.')) return name;
+  return name == '@' ? origin + '.' : name + '.' + origin + '.';
+}
+
+// The SOA with fully qualified server and mailbox names, as pdnsutil expects
+function normalizeSoa(record, origin) {
+  const fields = record.data.split(/\s+/);
+  fields[0] = qualifyName(fields[0], origin);
+  fields[1] = qualifyName(fields[1], origin);
+  return Object.assign({}, record, { data: fields.join(' ') });
+}
+
+// The BIND export reworked for pdnsutil load-zone: one SOA at the top, no
+// Cloudflare comments such as the cf-proxied tags, and real TTLs in place of
+// the automatic one
+module.exports = {
+  extension: 'zone',
+  comment: ';',
+  render(zone) {
+    const parsed = parseZone(zone.text, zone.name);
+    const soa = parsed.records.filter((record) => record.type == 'SOA');
+    if (soa.length != 1) {
+      throw new Error('Expected one SOA record, found ' + soa.length);
+    }
+    const records = [normalizeSoa(soa[0], parsed.origin)].concat(
+      parsed.records.filter((record) => record.type != 'SOA')
+    );
+
+    return (
+      '$ORIGIN ' +
+      parsed.origin +
+      '.\n' +
+      records
+        .map((record) =>
+          Object.assign({}, record, {
+            ttl: record.ttl == 1 ? AUTOMATIC_TTL : record.ttl,
+          })
+        )
+        .map((record) => recordLine(record) + '\n')
+        .join('')
+    );
+  },
+};
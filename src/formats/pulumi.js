@@ -0,0 +1,67 @@
+const { isManaged, recordArguments, resourceName } = require('./terraform');
+
+// Pulumi's argument names are the Terraform ones in camelCase
+function camelCase(name) {
+  return name.replace(/_([a-z])/g, (match, letter) => letter.toUpperCase());
+}
+
+// An object literal from [name, value] pairs whose values are already code
+function tsObject(pairs, indent) {
+  return (
+    '{\n' +
+    pairs
+      .map((pair) => indent + '  ' + camelCase(pair[0]) + ': ' + pair[1])
+      .map((line) => line + ',\n')
+      .join('') +
+    indent +
+    '}'
+  );
+}
+
+function tsValues(pairs) {
+  return pairs.map((pair) => [pair[0], JSON.stringify(pair[1])]);
+}
+
+function recordResource(record, zone, used) {
+  const resource = recordArguments(record);
+  const args = tsValues([['zone_id', zone.id]].concat(resource.args));
+  if (resource.data) {
+    args.push(['data', tsObject(tsValues(resource.data), '  ')]);
+  }
+  // The import option adopts the existing record on the first pulumi up
+  const options = record.id
+    ? ', { import: ' + JSON.stringify(zone.id + '/' + record.id) + ' }'
+    : '';
+  return (
+    'new cloudflare.Record(' +
+    JSON.stringify(resourceName(record, used)) +
+    ', ' +
+    tsObject(args, '') +
+    options +
+    ');\n'
+  );
+}
+
+module.exports = {
+  extension: 'ts',
+  comment: '//',
+  source: 'records',
+  render(zone) {
+    return this.renderAll([zone]);
+  },
+  renderAll(zones) {
+    const used = new Set();
+    const resources = [];
+    zones.forEach((zone) => {
+      zone.records
+        .filter((record) => !isManaged(record, zone))
+        .forEach((record) => {
+          resources.push(recordResource(record, zone, used));
+        });
+    });
+    return (
+      'import * as cloudflare from "@pulumi/cloudflare";\n\n' +
+      resources.join('\n')
+    );
+  },
+};
@@ -0,0 +1,32 @@
+// Record objects as the dns_records API returns them, less the fields that
+// repeat the zone itself. Every record has a comment and tags, even where the
+// API leaves them out.
+function recordObject(record) {
+  const fields = Object.assign({}, record);
+  delete fields.zone_id;
+  delete fields.zone_name;
+  if (fields.comment === undefined) fields.comment = null;
+  if (fields.tags === undefined) fields.tags = [];
+  return fields;
+}
+
+function zoneDocument(zone) {
+  return {
+    zone: zone.name,
+    id: zone.id,
+    records: zone.records.map(recordObject),
+  };
+}
+
+// Record name relative to the zone, '' for the apex
+function relativeName(record, zone) {
+  return record.name == zone.name
+    ? ''
+    : record.name.slice(0, -(zone.name.length + 1));
+}
+
+function fqdn(name) {
+  return name.endsWith('.') ? name : name + '.';
+}
+
+module.exports = { recordObject, zoneDocument, relativeName, fqdn };
@@ -0,0 +1,86 @@
+// Shared pieces of the human-readable report formats
+
+// Types that can be proxied, and so expose the origin when they are not
+const PROXIABLE_TYPES = ['A', 'AAAA', 'CNAME'];
+
+function ttlText(ttl) {
+  return ttl == 1 ? 'Auto' : String(ttl);
+}
+
+// Records grouped by type, in order of type name
+function recordGroups(zone) {
+  const groups = {};
+  zone.records.forEach((record) => {
+    if (!groups[record.type]) groups[record.type] = [];
+    groups[record.type].push(record);
+  });
+  return Object.keys(groups)
+    .sort()
+    .map((type) => ({ type: type, records: groups[type] }));
+}
+
+// The columns shown for each record, with Priority only where it applies
+function recordColumns(records) {
+  const columns = [
+    ['Name', (record) => record.name],
+    ['Content', (record) => record.content],
+    ['TTL', (record) => ttlText(record.ttl)],
+  ];
+  if (records.some((record) => record.priority !== undefined)) {
+    columns.push(['Priority', (record) => record.priority]);
+  }
+  if (records.some((record) => PROXIABLE_TYPES.includes(record.type))) {
+    columns.push(['Proxied', (record) => (record.proxied ? 'Yes' : 'No')]);
+  }
+  if (records.some((record) => record.comment)) {
+    columns.push(['Comment', (record) => record.comment]);
+  }
+  return columns.map((column) => ({
+    title: column[0],
+    value: (record) => {
+      const value = column[1](record);
+      return value === undefined || value === null ? '' : String(value);
+    },
+  }));
+}
+
+// Things worth a second look in a handover
+function zoneFindings(zone) {
+  const findings = [];
+  const records = zone.records;
+  const txt = (name) =>
+    records.filter((record) => record.type == 'TXT' && record.name == name);
+
+  const exposed = records.filter(
+    (record) => ['A', 'AAAA'].includes(record.type) && !record.proxied
+  );
+  if (exposed.length) {
+    findings.push(
+      exposed.length +
+        ' A/AAAA record(s) are DNS only, so their addresses are public: ' +
+        exposed.map((record) => record.name).join(', ')
+    );
+  }
+  if (records.some((record) => record.type == 'MX')) {
+    if (!txt(zone.name).some((record) => /^v=spf1/.test(record.content))) {
+      findings.push('The zone receives mail but has no SPF record');
+    }
+    if (!txt('_dmarc.' + zone.name).length) {
+      findings.push('The zone receives mail but has no DMARC record');
+    }
+  }
+  const wildcards = records.filter((record) => record.name.startsWith('*.'));
+  if (wildcards.length) {
+    findings.push(
+      'Wildcard records: ' +
+        wildcards.map((record) => record.type + ' ' + record.name).join(', ')
+    );
+  }
+  const short = records.filter((record) => record.ttl > 1 && record.ttl < 60);
+  if (short.length) {
+    findings.push(short.length + ' record(s) have a TTL under a minute');
+  }
+  return findings;
+}
+
+module.exports = { ttlText, recordGroups, recordColumns, zoneFindings };
@@ -0,0 +1,104 @@
+const log = require('../log');
+const { relativeName, fqdn } = require('./records');
+
+// Route 53 holds at most 255 characters in each string of a TXT value
+const MAX_STRING = 255;
+
+function quoted(text) {
+  if (/^".*"$/.test(text)) return text;
+  const strings = [];
+  for (let i = 0; i < text.length || i == 0; i += MAX_STRING) {
+    strings.push(text.slice(i, i + MAX_STRING));
+  }
+  return strings
+    .map((string) => '"' + string.replace(/(["\\])/g, '\\$1') + '"')
+    .join(' ');
+}
+
+// Types Route 53 supports, and how each value is written
+const VALUE_TYPES = {
+  A: (record) => record.content,
+  AAAA: (record) => record.content,
+  CNAME: (record) => fqdn(record.content),
+  NS: (record) => fqdn(record.content),
+  PTR: (record) => fqdn(record.content),
+  TXT: (record) => quoted(record.content),
+  SPF: (record) => quoted(record.content),
+  MX: (record) => record.priority + ' ' + fqdn(record.content),
+  SRV: (record) =>
+    [
+      record.data.priority,
+      record.data.weight,
+      record.data.port,
+      fqdn(record.data.target),
+    ].join(' '),
+  CAA: (record) =>
+    [record.data.flags, record.data.tag, quoted(record.data.value)].join(' '),
+};
+
+// Cloudflare's automatic TTL has no Route 53 equivalent
+const AUTO_TTL = 1;
+const DEFAULT_TTL = 300;
+
+// Route 53 makes its own apex NS records, and a CNAME at the apex only works
+// on Cloudflare, through CNAME flattening
+function supported(record, zone) {
+  const apex = relativeName(record, zone) == '';
+  if (apex && ['NS', 'CNAME'].includes(record.type)) return false;
+  return Boolean(VALUE_TYPES[record.type]);
+}
+
+function changeBatch(zone) {
+  const sets = new Map();
+  const skipped = [];
+  zone.records.forEach((record) => {
+    if (!supported(record, zone)) {
+      if (record.type != 'NS') skipped.push(record.type + ' ' + record.name);
+      return;
+    }
+    const key = record.name + ' ' + record.type;
+    if (!sets.has(key)) sets.set(key, []);
+    sets.get(key).push(record);
+  });
+
+  const changes = Array.from(sets.values()).map((records) => {
+    const first = records[0];
+    return {
+      Action: 'UPSERT',
+      ResourceRecordSet: {
+        Name: fqdn(first.name),
+        Type: first.type,
+        TTL: first.ttl == AUTO_TTL ? DEFAULT_TTL : first.ttl,
+        ResourceRecords: records.map((record) => ({
+          Value: VALUE_TYPES[first.type](record),
+        })),
+      },
+    };
+  });
+  // Route 53 rejects comments over 256 characters, so the batch only counts
+  // the skipped records and the list goes to the log
+  let comment = 'Exported from Cloudflare';
+  if (skipped.length) {
+    comment += ', skipped ' + skipped.length + ' unsupported records';
+    log.warn(
+      zone.name + ': Not supported by Route 53, skipped ' + skipped.join(', ')
+    );
+  }
+  return { Comment: comment, Changes: changes };
+}
+
+// A change batch per zone, for aws route53 change-resource-record-sets
+module.exports = {
+  VALUE_TYPES,
+  extension: 'json',
+  source: 'records',
+  render(zone) {
+    return JSON.stringify(changeBatch(zone), null, 2) + '\n';
+  },
+  // Keyed by zone, as each zone needs its own hosted zone ID
+  renderAll(zones) {
+    const batches = {};
+    zones.forEach((zone) => (batches[zone.name] = changeBatch(zone)));
+    return JSON.stringify(batches, null, 2) + '\n';
+  },
+};
@@ -0,0 +1,147 @@
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { spawnSync } = require('child_process');
+
+const SCHEMA = `
+CREATE TABLE zones (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  status TEXT,
+  plan TEXT,
+  account_id TEXT,
+  account TEXT
+);
+CREATE TABLE records (
+  id TEXT,
+  zone_id TEXT NOT NULL REFERENCES zones (id),
+  zone TEXT NOT NULL,
+  name TEXT NOT NULL,
+  type TEXT NOT NULL,
+  content TEXT,
+  ttl INTEGER,
+  priority INTEGER,
+  proxied INTEGER,
+  comment TEXT,
+  tags TEXT,
+  data TEXT,
+  created_on TEXT,
+  modified_on TEXT
+);
+CREATE INDEX records_zone ON records (zone_id);
+CREATE INDEX records_type_content ON records (type, content);
+`;
+
+function sqlValue(value) {
+  if (value === null || value === undefined) return 'NULL';
+  if (typeof value == 'boolean') return value ? '1' : '0';
+  if (typeof value == 'number') return String(value);
+  // Lists and objects such as tags and data are stored as JSON
+  const text = typeof value == 'object' ? JSON.stringify(value) : String(value);
+  return "'" + text.replace(/'/g, "''") + "'";
+}
+
+function insert(table, row) {
+  return (
+    'INSERT INTO ' +
+    table +
+    ' (' +
+    Object.keys(row).join(', ') +
+    ') VALUES (' +
+    Object.keys(row)
+      .map((key) => sqlValue(row[key]))
+      .join(', ') +
+    ');\n'
+  );
+}
+
+function sqlScript(zones) {
+  const statements = ['BEGIN;\n', SCHEMA];
+  zones.forEach((zone) => {
+    statements.push(
+      insert('zones', {
+        id: zone.id,
+        name: zone.name,
+        status: zone.status,
+        plan: zone.plan,
+        account_id: zone.account_id,
+        account: zone.account,
+      })
+    );
+    zone.records.forEach((record) => {
+      statements.push(
+        insert('records', {
+          id: record.id,
+          zone_id: zone.id,
+          zone: zone.name,
+          name: record.name,
+          type: record.type,
+          content: record.content,
+          ttl: record.ttl,
+          priority: record.priority,
+          proxied: record.proxied,
+          comment: record.comment,
+          tags: record.tags,
+          data: record.data,
+          created_on: record.created_on,
+          modified_on: record.modified_on,
+        })
+      );
+    });
+  });
+  statements.push('COMMIT;\n');
+  return statements.join('');
+}
+
+// node:sqlite where this Node.js has it, otherwise the sqlite3 command
+function runScript(file, script) {
+  let sqlite = null;
+  try {
+    sqlite = require('node:sqlite');
+  } catch (error) {
+    // Older Node.js
+  }
+  if (sqlite) {
+    const database = new sqlite.DatabaseSync(file);
+    database.exec(script);
+    database.close();
+    return;
+  }
+
+  const result = spawnSync('sqlite3', [file], {
+    input: script,
+    encoding: 'utf8',
+    maxBuffer: Infinity,
+  });
+  if (result.error) {
+    throw new Error(
+      'The sqlite format needs Node.js 22.5 or later, or the sqlite3 ' +
+        'command: ' +
+        result.error.message
+    );
+  } else if (result.status !== 0) {
+    throw new Error('sqlite3 failed: ' + result.stderr.trim());
+  }
+}
+
+// Every zone goes into one database, written to a single file
+module.exports = {
+  extension: 'sqlite',
+  source: 'records',
+  combined: true,
+  render(zone) {
+    return this.renderAll([zone]);
+  },
+  renderAll(zones) {
+    const prefix = path.join(os.tmpdir(), 'export-cloudflare-dns-');
+    const dir = fs.mkdtempSync(prefix);
+    const file = path.join(dir, 'zones.sqlite');
+    try {
+      runScript(file, sqlScript(zones));
+      return fs.readFileSync(file);
+    } finally {
+      if (fs.existsSync(file)) fs.unlinkSync(file);
+      fs.rmdirSync(dir);
+    }
+  },
+};
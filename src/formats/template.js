@@ -0,0 +1,50 @@
+const path = require('path');
+const { version } = require('../../package.json');
+const { headerTimestamp } = require('../header');
+const { loadTemplate } = require('../template');
+const { recordObject, relativeName } = require('./records');
+
+// What a template sees for each zone: the zone's own fields, the same again
+// under zone, and its records with the API fields plus relative_name
+function zoneView(zone, options) {
+  const fields = {
+    id: zone.id,
+    name: zone.name,
+    status: zone.status,
+    plan: zone.plan,
+    account: zone.account,
+    account_id: zone.account_id,
+  };
+  return Object.assign({}, fields, {
+    zone: fields,
+    records: zone.records.map((record) =>
+      Object.assign(recordObject(record), {
+        relative_name: relativeName(record, zone),
+      })
+    ),
+    exported_at: headerTimestamp(options.exportedAt),
+    version: version,
+  });
+}
+
+// Files take their extension from the template's name, less any .tmpl, so
+// hosts.txt.tmpl writes domainname.txt
+function templateExtension(file) {
+  const name = path.basename(file).replace(/\.(tmpl|mustache)$/, '');
+  const extension = path.extname(name).slice(1);
+  return extension ? extension : 'txt';
+}
+
+// Renders each zone through the --template file, set with use()
+module.exports = {
+  extension: 'txt',
+  source: 'records',
+  template: null,
+  use(file) {
+    this.template = loadTemplate(file);
+    this.extension = templateExtension(file);
+  },
+  render(zone, options) {
+    return this.template(zoneView(zone, options));
+  },
+};
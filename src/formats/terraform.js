@@ -0,0 +1,142 @@
+// Types Cloudflare can proxy, which take a proxied argument
+const PROXIABLE_TYPES = ['A', 'AAAA', 'CNAME'];
+
+// Records Cloudflare manages itself and Terraform cannot create
+function isManaged(record, zone) {
+  return (
+    record.type == 'SOA' || (record.type == 'NS' && record.name == zone.name)
+  );
+}
+
+// Escape template sequences so values are taken literally
+function hclString(value) {
+  return JSON.stringify(String(value))
+    .replace(/\$\{/g, '$$${')
+    .replace(/%\{/g, '%%{');
+}
+
+function hclValue(value) {
+  return typeof value == 'number' || typeof value == 'boolean'
+    ? String(value)
+    : hclString(value);
+}
+
+// Render a block with its = signs aligned, as terraform fmt does. Nested
+// blocks follow the attributes, indented.
+function hclBlock(header, attributes, blocks) {
+  const width = Math.max(...attributes.map((pair) => pair[0].length));
+  const nested = (blocks ? blocks : []).map(
+    (block) => '\n' + block.replace(/^(?=.)/gm, '  ')
+  );
+  return (
+    header +
+    ' {\n' +
+    attributes
+      .map((pair) => '  ' + pair[0].padEnd(width) + ' = ' + pair[1] + '\n')
+      .join('') +
+    nested.join('') +
+    '}\n'
+  );
+}
+
+// SRV, CAA and other structured types carry their fields in a data block
+// instead of content
+function recordData(record) {
+  if (!record.data || typeof record.data != 'object') return null;
+  const fields = Object.keys(record.data)
+    .filter((key) => record.data[key] !== null)
+    .filter((key) => record.data[key] !== undefined)
+    .map((key) => [key, record.data[key]]);
+  return fields.length ? fields : null;
+}
+
+// The arguments of a Cloudflare record resource other than the zone, as
+// [name, value] pairs, plus the data fields if it has any
+function recordArguments(record) {
+  const data = recordData(record);
+  const args = [
+    ['name', record.name],
+    ['type', record.type],
+  ];
+  if (!data) args.push(['content', record.content]);
+  // A ttl of 1 is Cloudflare's automatic TTL, the only one proxied records take
+  args.push(['ttl', record.proxied ? 1 : record.ttl]);
+  // SRV records keep their priority in the data block
+  const priority = record.priority !== undefined && record.priority !== null;
+  if (priority && !(data && record.data.priority !== undefined)) {
+    args.push(['priority', record.priority]);
+  }
+  if (record.proxiable || PROXIABLE_TYPES.includes(record.type)) {
+    args.push(['proxied', record.proxied ? true : false]);
+  }
+  return { args: args, data: data };
+}
+
+// Terraform resource names from the record name and type, e.g. www_example_com_a
+function resourceName(record, used) {
+  const base = (record.name + '_' + record.type)
+    .toLowerCase()
+    .replace(/[^a-z0-9_-]/g, '_')
+    .replace(/^([^a-z_])/, '_$1');
+  let name = base;
+  for (let i = 2; used.has(name); i++) {
+    name = base + '_' + i;
+  }
+  used.add(name);
+  return name;
+}
+
+function recordResource(record, zone, used) {
+  const resource = recordArguments(record);
+  const attributes = [['zone_id', hclString(zone.id)]].concat(
+    resource.args.map((pair) => [pair[0], hclValue(pair[1])])
+  );
+  const data = resource.data
+    ? hclBlock(
+        'data',
+        resource.data.map((pair) => [pair[0], hclValue(pair[1])])
+      )
+    : null;
+  const name = resourceName(record, used);
+  const block = hclBlock(
+    'resource "cloudflare_record" "' + name + '"',
+    attributes,
+    data ? [data] : []
+  );
+  // Import blocks let terraform plan adopt the existing record into state
+  if (!record.id) return block;
+  return (
+    block +
+    '\n' +
+    hclBlock('import', [
+      ['to', 'cloudflare_record.' + name],
+      ['id', hclString(zone.id + '/' + record.id)],
+    ])
+  );
+}
+
+// Resource names are shared across zones rendered with the same used set
+function zoneResources(zone, used) {
+  return zone.records
+    .filter((record) => !isManaged(record, zone))
+    .map((record) => recordResource(record, zone, used))
+    .join('\n');
+}
+
+module.exports = {
+  hclString,
+  hclBlock,
+  isManaged,
+  recordArguments,
+  resourceName,
+  extension: 'tf',
+  comment: '#',
+  source: 'records',
+  render(zone) {
+    return zoneResources(zone, new Set());
+  },
+  renderAll(zones) {
+    const used = new Set();
+    return zones.map((zone) => zoneResources(zone, used)).join('\n');
+  },
+};
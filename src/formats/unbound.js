@@ -0,0 +1,43 @@
+const { fqdn } = require('./records');
+const { VALUE_TYPES } = require('./route53');
+
+// Cloudflare's automatic TTL, which unbound needs as a number
+const AUTO_TTL = 1;
+const DEFAULT_TTL = 300;
+
+// Values go in double quotes, with the quotes of TXT data and any
+// backslashes escaped
+function quoted(text) {
+  return '"' + text.replace(/(["\\])/g, '\\$1') + '"';
+}
+
+// A server clause with a transparent local-zone, so names the export does
+// not cover still resolve as usual
+module.exports = {
+  extension: 'conf',
+  comment: '#',
+  source: 'records',
+  render(zone) {
+    const lines = [
+      'server:',
+      '  local-zone: ' + quoted(fqdn(zone.name)) + ' transparent',
+    ];
+    zone.records.forEach((record) => {
+      if (!VALUE_TYPES[record.type]) {
+        lines.push(
+          '  # Not supported, skipped: ' + record.type + ' ' + record.name
+        );
+        return;
+      }
+      const data = [
+        fqdn(record.name),
+        record.ttl == AUTO_TTL ? DEFAULT_TTL : record.ttl,
+        'IN',
+        record.type,
+        VALUE_TYPES[record.type](record),
+      ];
+      lines.push('  local-data: ' + quoted(data.join(' ')));
+    });
+    return lines.join('\n') + '\n';
+  },
+};
@@ -0,0 +1,150 @@
+const { zoneDocument } = require('./records');
+
+// Quote strings YAML would otherwise read as another type or misparse.
+// Line breaks and other control characters are only written escaped, in
+// double quotes.
+function scalar(value) {
+  if (value === null || value === undefined) return 'null';
+  if (typeof value != 'string') return String(value);
+  const plain =
+    /^[A-Za-z0-9_./][^:#\x00-\x1f\x7f\x85\u2028\u2029]*$/.test(value) &&
+    !/\s$/.test(value) &&
+    !/^(true|false|yes|no|on|off|null|~|[-+]?[\d.]+(e[-+]?\d+)?)$/i.test(value);
+  return plain ? value : JSON.stringify(value);
+}
+
+function toYaml(value, indent) {
+  indent = indent ? indent : '';
+  if (Array.isArray(value)) {
+    if (value.length == 0) return ' []\n';
+    return (
+      '\n' +
+      value
+        .map((item) => {
+          const body = toYaml(item, indent + '  ');
+          // Put the first key of a mapping on the dash line
+          return body.startsWith('\n')
+            ? indent + '- ' + body.slice(indent.length + 3)
+            : indent + '-' + body;
+        })
+        .join('')
+    );
+  } else if (value && typeof value == 'object') {
+    const keys = Object.keys(value);
+    if (keys.length == 0) return ' {}\n';
+    return (
+      '\n' +
+      keys
+        .map(
+          (key) =>
+            indent + scalar(key) + ':' + toYaml(value[key], indent + '  ')
+        )
+        .join('')
+    );
+  }
+  return ' ' + scalar(value) + '\n';
+}
+
+function readScalar(text) {
+  if (text.startsWith('"')) return JSON.parse(text);
+  if (text == 'null' || text == '~') return null;
+  if (text == 'true' || text == 'false') return text == 'true';
+  if (text == '[]') return [];
+  if (text == '{}') return {};
+  if (/^[-+]?[\d.]+(e[-+]?\d+)?$/i.test(text) && !isNaN(Number(text))) {
+    return Number(text);
+  }
+  return text;
+}
+
+// The key of a mapping entry and the text after its colon, or null if the
+// line has none. Plain scalars never contain a colon, see scalar().
+function splitKey(text) {
+  const quoted = /^("(?:[^"\\]|\\.)*"):(?: |$)/.exec(text);
+  if (quoted) {
+    return [JSON.parse(quoted[1]), text.slice(quoted[0].length).trim()];
+  }
+  const plain = /^([^":\s][^:]*):(?: |$)/.exec(text);
+  return plain ? [plain[1], text.slice(plain[0].length).trim()] : null;
+}
+
+// Read the block of lines at this indent from lines[start], returning the
+// value and the index of the line after it
+function readBlock(lines, start, indent) {
+  const sequence = /^-( |$)/.test(lines[start].text);
+  const value = sequence ? [] : {};
+  let i = start;
+  while (i < lines.length && lines[i].indent == indent) {
+    const line = lines[i];
+    let key = null;
+    let rest;
+    if (sequence) {
+      if (!/^-( |$)/.test(line.text)) break;
+      rest = line.text.slice(1).trim();
+      // A mapping or sequence may start on the dash line, indented past it
+      if (splitKey(rest) || /^-( |$)/.test(rest)) {
+        lines[i] = { indent: indent + 2, text: rest };
+        const item = readBlock(lines, i, indent + 2);
+        value.push(item[0]);
+        i = item[1];
+        continue;
+      }
+    } else {
+      const entry = splitKey(line.text);
+      if (!entry) throw new Error('Expected a key on line ' + line.number);
+      key = entry[0];
+      rest = entry[1];
+    }
+    i++;
+    let item;
+    if (rest) {
+      item = readScalar(rest);
+    } else if (i < lines.length && lines[i].indent > indent) {
+      const nested = readBlock(lines, i, lines[i].indent);
+      item = nested[0];
+      i = nested[1];
+    } else {
+      item = null;
+    }
+    if (sequence) value.push(item);
+    else value[key] = item;
+  }
+  return [value, i];
+}
+
+// Read back YAML as toYaml writes it. This is not a general YAML parser.
+function fromYaml(text) {
+  const lines = [];
+  text.split(/\r?\n/).forEach((line, index) => {
+    const trimmed = line.trim();
+    if (!trimmed || trimmed.startsWith('#') || trimmed == '---') return;
+    lines.push({
+      indent: line.length - line.trimStart().length,
+      text: trimmed,
+      number: index + 1,
+    });
+  });
+  if (lines.length == 0) return null;
+  if (lines.length == 1 && !splitKey(lines[0].text)) {
+    return readScalar(lines[0].text);
+  }
+  const result = readBlock(lines, 0, lines[0].indent);
+  if (result[1] < lines.length) {
+    throw new Error('Unexpected indent on line ' + lines[result[1]].number);
+  }
+  return result[0];
+}
+
+module.exports = {
+  extension: 'yaml',
+  comment: '#',
+  source: 'records',
+  toYaml: (value) => toYaml(value).replace(/^\n/, ''),
+  fromYaml: fromYaml,
+  render(zone) {
+    return this.toYaml(zoneDocument(zone));
+  },
+  renderAll(zones) {
+    return this.toYaml(zones.map(zoneDocument));
+  },
+};
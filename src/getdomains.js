@@ -1,144 +1,2014 @@
+#!/usr/bin/env node
 // "use strict";
 
-const axios = require('axios');
+const { installRedaction, registerSecret } = require('./redact');
+
+installRedaction();
+
 const fs = require('fs');
+const path = require('path');
 const { exit } = require('process');
+const keyring = require('./keyring');
+const {
+  PROGRAM,
+  EXIT_CODES,
+  OPTIONS,
+  camelCase,
+  configValue,
+  parseArgs,
+  helpText,
+} = require('./cli');
+const {
+  defaultConfigPaths,
+  defaultOutputDir,
+  loadConfig,
+} = require('./config');
+const log = require('./log');
+const { runConcurrently, createThrottle } = require('./concurrency');
+const { renderTable } = require('./table');
+const { createProgress } = require('./progress');
+const { configureHttp } = require('./http');
+const { versionText } = require('./version');
+const { parseList, matchesAny } = require('./filters');
+const { FORMATS, FORMAT_NAMES, renderZones } = require('./formats');
+const { fetchExtras, fetchAccounts } = require('./extras');
+const { parseTime, dnsAuditEntries, entryZone } = require('./audit');
+const {
+  readSnapshot,
+  copyRecords,
+  parseRewrites,
+  rewriteRecord,
+  createZone,
+  existingRecord,
+  planChanges,
+  describeChange,
+  planDiff,
+  throttledChange,
+  recordsDigest,
+  planDocument,
+  readPlan,
+} = require('./restore');
+const {
+  parseZone,
+  splitZoneText,
+  compareRecords,
+  canonicalZone,
+  stripComments,
+  annotateZone,
+} = require('./zonefile');
+const {
+  directoryWriter,
+  stdoutWriter,
+  compressingWriter,
+  archiveWriter,
+  archiveName,
+} = require('./output');
+const { CODECS } = require('./compress');
+const {
+  loadHeaderTemplate,
+  renderHeader,
+  headerTimestamp,
+} = require('./header');
+const {
+  AUTH_MODE_LABELS,
+  apiEndpoint,
+  createClient,
+  authMode,
+  isValidServiceKey,
+  apiErrorMessages,
+  verifyCredentials,
+} = require('./cloudflare');
+const { readVaultToken } = require('./vault');
+const { isAwsReference, readAwsSecret } = require('./aws');
+const { ask, askHidden } = require('./prompt');
+const {
+  isOnePasswordReference,
+  readOnePasswordSecret,
+} = require('./onepassword');
+
+// Profile keys holding credentials, and the variables they set
+const PROFILE_VARIABLES = {
+  api_token: 'CLOUDFLARE_API_TOKEN',
+  api_token_file: 'CLOUDFLARE_API_TOKEN_FILE',
+  api_key: 'CLOUDFLARE_API_KEY',
+  email: 'CLOUDFLARE_USER_EMAIL',
+  user_service_key: 'CLOUDFLARE_USER_SERVICE_KEY',
+  vault_path: 'CLOUDFLARE_VAULT_PATH',
+};
+
+const DEFAULT_CONCURRENCY = 4;
+
+// Record writes a second, within the API's limit of 1200 requests in five
+// minutes with room for other clients
+const DEFAULT_WRITE_RATE = 3;
+
+// Allowed difference between the local clock and Cloudflare's, in seconds
+const MAX_CLOCK_SKEW = 300;
+
+// Names used by Terraform, flarectl and cf-terraforming
+const ENV_ALIASES = {
+  CF_API_TOKEN: 'CLOUDFLARE_API_TOKEN',
+  CF_API_KEY: 'CLOUDFLARE_API_KEY',
+  CF_API_EMAIL: 'CLOUDFLARE_USER_EMAIL',
+  CF_API_USER_SERVICE_KEY: 'CLOUDFLARE_USER_SERVICE_KEY',
+};
+
+// Commands that take a zone and a file of the records it should have
+const SNAPSHOT_COMMANDS = ['import', 'sync', 'diff', 'plan'];
+
+const { command, options, positionals: args } = parseCommandLine();
+// Older versions took the env file as the only argument
+const legacyEnvFile =
+  command == 'export' && args[0] && fs.existsSync(args[0]) ? args[0] : null;
+// apply takes the zone from its plan file
+let zoneArgument =
+  (command == 'export' && !legacyEnvFile) || SNAPSHOT_COMMANDS.includes(command)
+    ? args[0]
+    : null;
+// The --header-template text and the export time it is stamped with
+let headerTemplate = null;
+// The records of the snapshot file, or the plan to apply, read before
+// connecting
+let snapshotRecords = null;
+let savedPlan = null;
+const runStarted = new Date();
+const config = readConfig();
+applyOptionDefaults();
+log.setLevel(options.quiet, options.verbose);
+log.setColor(!options.noColor && !process.env.NO_COLOR);
+configureRequests();
+
+main();
+
+async function main() {
+  if (options.generateMan) {
+    process.stdout.write(require('./man').manPage());
+    return;
+  } else if (options.version) {
+    process.stdout.write(versionText(apiEndpoint(), options.proxy));
+    return;
+  } else if (options.help || command == 'help') {
+    const topic = command == 'help' ? args[0] : command;
+    const named = process.argv.slice(2).includes(topic);
+    process.stdout.write(helpText(named ? topic : null));
+    return;
+  } else if (command == 'credentials') {
+    manageCredentials(args[0]);
+    return;
+  } else if (command == 'init') {
+    await initConfig();
+    return;
+  } else if (command == 'doctor') {
+    await runDoctor();
+    return;
+  } else if (command == 'migrate') {
+    // Each side has the credentials of its own profile
+    loadEnvFile();
+    try {
+      await migrateZone(args[0]);
+    } catch (error) {
+      apiErrorMessages(error).forEach((message) => log.error(message));
+      process.exit(exitCodeFor(error));
+    }
+    return;
+  } else if (command == 'list' || toStdout()) {
+    log.useStderr();
+  }
+
+  await checkEnvironment();
 
-const args = process.argv.slice(2)
-
-const CLOUDFLARE_ENDPOINT = 'https://api.cloudflare.com/client/v4/';
-let domainList = [];
-
-checkEnvironment();
-
-// Fetch data from Cloudflare
-console.info('Getting List of domains from Cloudflare');
-console.info('=======================================\n');
-
-// Get domain names from Cloudflare
-getDomains();
-
-function getDomains(getPage) {
-  axios
-    .get('zones', {
-      baseURL: CLOUDFLARE_ENDPOINT,
-      headers: {
-        'X-Auth-Email': process.env.CLOUDFLARE_USER_EMAIL,
-        'X-Auth-Key': process.env.CLOUDFLARE_API_KEY,
-        'Content-Type': 'application/json',
-      },
-      params: {
-        page: getPage ? getPage : 1,
-      },
-    })
-    .then(function (response, domainList) {
-      // Process Domain list
-      if (response.data.success) {
-        const pageInfo = response.data.result_info;
-
-        console.log('Fetching batch of ' + pageInfo.count + ' DNS records ...');
-
-        domainList = addDomainsToList(response.data);
-
-        // Check for more pages in domain name list
-        if (pageInfo.page < pageInfo.total_pages) {
-          getDomains(pageInfo.page + 1);
+  const client = createClient();
+  try {
+    await verifyCredentials(client);
+  } catch (error) {
+    log.error(error.message);
+    process.exit(EXIT_CODES.AUTH);
+  }
+
+  // Fetch data from Cloudflare
+  log.verbose('Getting List of domains from Cloudflare');
+  log.verbose('=======================================\n');
+
+  try {
+    if (command == 'clone') {
+      await cloneZone(client);
+      return;
+    }
+    // Get domain names from Cloudflare
+    let domains = zoneArgument
+      ? await getDomain(client, zoneArgument)
+      : await getDomains(client);
+    await addOverrideDomains(domains);
+    if (zoneArgument && domains.length == 0) {
+      log.error('Zone ' + zoneArgument + ' not found');
+      process.exit(EXIT_CODES.NOTHING_TO_DO);
+    }
+    domains = selectDomains(domains);
+    if (command != 'list' && domains.length == 0) {
+      log.warn('No zones to export.');
+      process.exit(EXIT_CODES.NOTHING_TO_DO);
+    }
+
+    if (command == 'list') {
+      await listDomains(domains, client);
+    } else if (command == 'audit') {
+      await exportAudit(domains, client);
+    } else if (command == 'import') {
+      await importZone(domains[0], client);
+    } else if (command == 'sync') {
+      await syncZone(domains[0], client);
+    } else if (command == 'diff') {
+      await diffZone(domains[0], client);
+    } else if (command == 'plan') {
+      await savePlan(domains[0], client);
+    } else if (command == 'apply') {
+      await applyPlan(domains[0], client);
+    } else if (options.dryRun) {
+      reportDryRun(domains);
+    } else {
+      await exportDomains(domains, client);
+    }
+  } catch (error) {
+    // ERROR HANDLING
+    apiErrorMessages(error).forEach((message) => {
+      log.error(message);
+    });
+    process.exit(exitCodeFor(error));
+  }
+}
+
+function exitCodeFor(error) {
+  const status = error.response ? error.response.status : null;
+  return status == 401 || status == 403 ? EXIT_CODES.AUTH : EXIT_CODES.FAILURE;
+}
+
+function concurrency() {
+  return options.concurrency ? options.concurrency : DEFAULT_CONCURRENCY;
+}
+
+async function exportDomains(domains, client) {
+  // Export Domain Records
+  log.verbose('Writing domain DNS files');
+  // Like head and tail, only name each zone on stdout when there is more than one
+  const separate = domains.length > 1;
+  const started = new Date();
+  const results = domains.map((domain) => ({
+    zone: domain.name,
+    id: domain.id,
+    status: 'skipped',
+  }));
+  const errors = [];
+  // With --single-file zones are kept, in order, and written together at the end
+  const zones = options.singleFile ? domains.map(() => null) : null;
+  const writer = zones ? null : createWriter();
+  // Other --include-* files go next to the --single-file
+  const extrasWriter = zones && !toStdout() ? singleFileWriter() : null;
+  // Formats with an index page keep the zones to list once all are written
+  const indexed =
+    writer && currentFormat().index ? domains.map(() => null) : null;
+  let written = null;
+  // A progress bar replaces the summary lines on a terminal
+  const bar =
+    process.stdout.isTTY && !toStdout() && !options.quiet && domains.length > 1
+      ? createProgress(domains.length, process.stdout)
+      : null;
+  log.setProgress(bar);
+
+  try {
+    await runConcurrently(domains, concurrency(), async (domain) => {
+      const result = results[domains.indexOf(domain)];
+      const zoneStarted = Date.now();
+      if (bar) bar.start(domain.name);
+      try {
+        const zoneClient = domain.client ? domain.client : client;
+        const zone = await fetchZone(domain, zoneClient);
+        // The records are still exported without the extras that failed
+        zone.extraErrors.forEach((message) =>
+          log.warn(domain.name + ': ' + message)
+        );
+        if (zone.extraErrors.length) result.extra_errors = zone.extraErrors;
+        if (zones) {
+          zones[domains.indexOf(domain)] = zone;
+          result.file = toStdout() ? '-' : options.singleFile;
+          writeExtras(domain, zone, extrasWriter);
         } else {
-          console.log('Fetchied ' + pageInfo.total_count + ' domains.');
-          return domainList;
+          Object.assign(result, writeZone(domain, zone, separate, writer));
+          if (indexed) indexed[domains.indexOf(domain)] = zone;
         }
-      } else {
-        console.log(response.errors.message);
+        result.status = 'exported';
+      } catch (error) {
+        result.status = 'failed';
+        result.error = apiErrorMessages(error).join(', ');
+        errors.push(error);
+        log.error(domain.name + ': ' + result.error);
+        // Without --fail-fast the remaining zones are still exported
+        if (options.failFast) throw error;
+      } finally {
+        result.duration_ms = Date.now() - zoneStarted;
+        if (bar) bar.tick();
+      }
+    });
+  } catch (error) {
+    // Already reported by the worker, no new zones are started after this
+  } finally {
+    if (bar) bar.clear();
+    log.setProgress(null);
+    // Written once the zones are, so it only counts those that exported
+    if (options.includeAccounts) {
+      const exported = domains.filter(
+        (domain, index) => results[index].status == 'exported'
+      );
+      try {
+        await writeAccounts(exported, client, writer ? writer : extrasWriter);
+      } catch (error) {
+        errors.push(error);
+        log.error('Accounts: ' + apiErrorMessages(error).join(', '));
       }
-    })
-    .then(function (domains) {
-      // Check to see if domains list is returned
-      if (domains) {
-        // Export Domain Records
-        console.log('Writing domain DNS files');
-        domains.forEach((domain) => {
-          exportDNS(domain);
-        });
-        console.log(
-          'Domain DNS records complete. Please check the /domains direcotry for your files'
+    }
+    let finished = true;
+    if (zones) {
+      finished = writeSingleFile(zones.filter((zone) => zone));
+      written = compressedName(options.singleFile);
+    } else if (writer) {
+      const indexWritten = indexed
+        ? writeIndex(writer, indexed.filter((zone) => zone))
+        : true;
+      written = indexWritten ? finishWriter(writer, started, results) : false;
+      finished = written !== false;
+    }
+    if (!finished) {
+      results
+        .filter((result) => result.status == 'exported')
+        .forEach((result) => (result.status = 'failed'));
+      errors.push(new Error('Writing ' + written + ' failed'));
+    }
+    if (options.summaryJson) {
+      writeSummary(options.summaryJson, started, results);
+    }
+  }
+
+  if (errors.length) {
+    const failed = results.filter((result) => result.status == 'failed');
+    log.error(
+      failed.length +
+        ' of ' +
+        results.length +
+        ' zones failed: ' +
+        failed.map((result) => result.zone).join(', ')
+    );
+    const exported = results.some((result) => result.status == 'exported');
+    process.exitCode = exported ? EXIT_CODES.PARTIAL : exitCodeFor(errors[0]);
+  } else if (written && !toStdout()) {
+    log.success('Domain DNS records complete. Written to ' + written);
+  } else if (!toStdout()) {
+    log.success(
+      'Domain DNS records complete. Please check the ' +
+        outputDir() +
+        ' direcotry for your files'
+    );
+  }
+}
+
+// The accounts.json of --include-accounts, in the top of the output
+async function writeAccounts(domains, client, writer) {
+  const accounts = await fetchAccounts(client, domains);
+  const file = writer.write(
+    'accounts.json',
+    JSON.stringify(accounts, null, 2) + '\n'
+  );
+  log.verbose('Wrote ' + accounts.length + ' accounts to ' + file);
+}
+
+// Where zone files go: the output directory, or an --archive of them, each
+// compressed with --compress. Plain stdout exports have no writer.
+function createWriter() {
+  if (toStdout() && !options.archive) return null;
+  let writer = toStdout() ? stdoutWriter() : directoryWriter(outputDir());
+  if (options.compress) writer = compressWriter(writer);
+  return options.archive ? archiveWriter(options.archive, writer) : writer;
+}
+
+function compressWriter(writer) {
+  return compressingWriter(writer, options.compress, options.compressLevel);
+}
+
+// A file name as it is written, with the --compress extension
+function compressedName(name) {
+  return options.compress
+    ? name + '.' + CODECS[options.compress].extension
+    : name;
+}
+
+// Returns the path written at the end, if any, or false if writing failed
+function finishWriter(writer, started, results) {
+  const exported = results.filter((result) => result.status == 'exported');
+  if (exported.length == 0) return null;
+  try {
+    return writer.finish({
+      created_at: started.toISOString(),
+      format: options.format ? options.format : 'bind',
+      zones: exported.map((result) => ({
+        zone: result.zone,
+        id: result.id,
+        file: result.file,
+      })),
+    });
+  } catch (error) {
+    log.error('Writing archive: ' + error.message);
+    return false;
+  }
+}
+
+// The index of formats such as html, false if writing it failed
+function writeIndex(writer, zones) {
+  if (zones.length == 0) return true;
+  try {
+    const format = currentFormat();
+    const name = format.indexFile
+      ? format.indexFile
+      : 'index.' + format.extension;
+    const file = writer.write(name, format.index(zones));
+    log.verbose('Wrote the index of ' + zones.length + ' zones to ' + file);
+    return true;
+  } catch (error) {
+    log.error('Writing index: ' + error.message);
+    return false;
+  }
+}
+
+// Writes next to the --single-file, or to stdout, with any --compress
+function singleFileWriter() {
+  const writer = toStdout()
+    ? stdoutWriter()
+    : directoryWriter(path.dirname(options.singleFile));
+  return options.compress ? compressWriter(writer) : writer;
+}
+
+// Write every exported zone to the --single-file path, false if it failed
+function writeSingleFile(zones) {
+  if (zones.length == 0) return true;
+  const writer = singleFileWriter();
+  try {
+    // Combined formats such as sqlite render to a Buffer, and take no header
+    const body = renderZones(currentFormat(), zones, renderOptions());
+    const contents = headerTemplate ? fileHeader(null) + body : body;
+    const file = writer.write(path.basename(options.singleFile), contents);
+    log.verbose('Exported ' + zones.length + ' zones to ' + file);
+    return true;
+  } catch (error) {
+    log.error('Writing file: ' + error.message);
+    return false;
+  }
+}
+
+function writeSummary(file, started, results) {
+  const count = (status) =>
+    results.filter((result) => result.status == status).length;
+  const summary = {
+    started_at: started.toISOString(),
+    finished_at: new Date().toISOString(),
+    duration_ms: Date.now() - started.getTime(),
+    exported: count('exported'),
+    failed: count('failed'),
+    skipped: count('skipped'),
+    zones: results,
+  };
+
+  try {
+    fs.writeFileSync(file, JSON.stringify(summary, null, 2) + '\n');
+  } catch (error) {
+    log.error('Writing summary: ' + error.message);
+  }
+}
+
+async function listDomains(domains, client) {
+  // The zone listing has no record counts, so ask for one record per zone
+  await runConcurrently(domains, concurrency(), async (domain) => {
+    const response = await (domain.client ? domain.client : client).get(
+      'zones/' + domain.id + '/dns_records',
+      { params: { per_page: 1 } }
+    );
+    domain.records = response.data.result_info.total_count;
+  });
+
+  const zones = domains.map((domain) => ({
+    name: domain.name,
+    id: domain.id,
+    status: domain.status,
+    plan: domain.plan,
+    records: domain.records,
+  }));
+  if (options.json) {
+    process.stdout.write(JSON.stringify(zones, null, 2) + '\n');
+  } else {
+    process.stdout.write(
+      renderTable(
+        ['NAME', 'ID', 'STATUS', 'PLAN', 'RECORDS'],
+        zones.map((zone) => [
+          zone.name,
+          zone.id,
+          zone.status,
+          zone.plan,
+          zone.records,
+        ])
+      )
+    );
+  }
+}
+
+// Save the DNS changes in the selected zones' accounts to one NDJSON file,
+// each line tagged with its account
+async function exportAudit(domains, client) {
+  const now = new Date();
+  const since = parseTime(options.since ? options.since : '7d', now);
+  const until = options.until ? parseTime(options.until, now) : now;
+  const names = domains.map((domain) => domain.name);
+  const lines = [];
+  const accounts = [];
+  for (const domain of domains) {
+    if (!domain.accountId || accounts.includes(domain.accountId)) continue;
+    accounts.push(domain.accountId);
+    const accountClient = domain.client ? domain.client : client;
+    const entries = await dnsAuditEntries(
+      accountClient,
+      domain.accountId,
+      since,
+      until
+    );
+    entries
+      // Other zones in the account were not selected
+      .filter((entry) => !entryZone(entry) || names.includes(entryZone(entry)))
+      .forEach((entry) =>
+        lines.push(
+          JSON.stringify(Object.assign({ account_id: domain.accountId }, entry))
+        )
+      );
+  }
+
+  const contents = lines.map((line) => line + '\n').join('');
+  if (toStdout()) {
+    process.stdout.write(contents);
+    return;
+  }
+  // Named after the window, so saving the next one keeps this one
+  const stamp = (date) => date.toISOString().replace(/[-:]|\.\d+/g, '');
+  const name = 'audit-' + stamp(since) + '-' + stamp(until) + '.ndjson';
+  const file = directoryWriter(outputDir()).write(name, contents);
+  log.success('Saved ' + lines.length + ' DNS changes to ' + file);
+}
+
+// Create the records of the import file that the zone does not have yet
+async function importZone(domain, client) {
+  const zoneClient = domain.client ? domain.client : client;
+  const existing = await getRecords(zoneClient, domain);
+  const changes = snapshotRecords
+    .filter((payload) => !existingRecord(payload, existing))
+    .map((payload) => ({ action: 'create', payload: payload }));
+  const present = snapshotRecords.length - changes.length;
+
+  if (options.dryRun) {
+    log.info('Dry run, nothing will be created.\n');
+    planDiff(changes, domain.name, args[1]).forEach((line) =>
+      log.info(line.text, DIFF_COLORS[line.sign])
+    );
+    log.info(
+      '\nWould create ' +
+        changes.length +
+        ' records in ' +
+        domain.name +
+        ', ' +
+        present +
+        ' already exist.'
+    );
+    return;
+  }
+
+  if (await applyChanges(domain, zoneClient, changes)) {
+    log.success(
+      'Created ' +
+        changes.length +
+        ' records in ' +
+        domain.name +
+        ', ' +
+        present +
+        ' already existed'
+    );
+  }
+}
+
+// Make the zone match the snapshot, once the plan is confirmed
+async function syncZone(domain, client) {
+  const zoneClient = domain.client ? domain.client : client;
+  const existing = await getRecords(zoneClient, domain);
+  const changes = planChanges(
+    snapshotRecords,
+    existing,
+    domain.name,
+    options.prune
+  );
+  if (changes.length == 0) {
+    log.success('Nothing to change in ' + domain.name);
+    return;
+  }
+  await confirmAndApply(domain, zoneClient, changes);
+}
+
+// Show every difference between the zone and the snapshot, as sync --prune
+// would remove them, without changing anything
+async function diffZone(domain, client) {
+  const zoneClient = domain.client ? domain.client : client;
+  const existing = await getRecords(zoneClient, domain);
+  const changes = planChanges(snapshotRecords, existing, domain.name, true);
+  if (changes.length == 0) {
+    log.success(domain.name + ' matches ' + args[1]);
+    return;
+  }
+  printPlan(
+    domain,
+    changes,
+    'Differences between ' + domain.name + ' and ' + args[1]
+  );
+}
+
+// Print the plan and apply it once it is confirmed
+async function confirmAndApply(domain, client, changes) {
+  printPlan(domain, changes);
+  if (options.dryRun) return;
+  if (!options.yes && !(await confirmChanges(domain, changes))) {
+    log.info('Nothing was changed.');
+    return;
+  }
+  if (await applyChanges(domain, client, changes)) {
+    log.success('Applied ' + changes.length + ' changes to ' + domain.name);
+  }
+}
+
+// Copy the records of one zone to another with the same credentials,
+// rewriting the names in them, e.g. for a staging copy of production
+async function cloneZone(client) {
+  const zones = [];
+  for (const name of [args[0], args[1]]) {
+    const found = await getDomain(client, name);
+    if (found.length == 0) {
+      log.error('Zone ' + name + ' not found');
+      process.exit(EXIT_CODES.NOTHING_TO_DO);
+    }
+    zones.push(found[0]);
+  }
+  const source = zones[0];
+  const target = zones[1];
+  const rewrites = options.rewrite
+    ? parseRewrites(parseList(options.rewrite))
+    : [[source.name, target.name]];
+  const records = (await getRecords(client, source)).map((record) =>
+    rewriteRecord(record, rewrites)
+  );
+  const changes = planChanges(
+    copyRecords(records, target.name),
+    await getRecords(client, target),
+    target.name,
+    false
+  );
+  if (changes.length == 0) {
+    log.success(target.name + ' already has every record of ' + source.name);
+    return;
+  }
+  await confirmAndApply(target, client, changes);
+}
+
+// Deleting records takes typing the zone's name, other changes a yes
+async function confirmChanges(domain, changes) {
+  if (!process.stdin.isTTY) {
+    log.error(command + ' asks before changing records, add --yes to skip it');
+    process.exit(EXIT_CODES.CONFIG);
+  }
+  const deletes = changes.filter((change) => change.action == 'delete');
+  if (deletes.length == 0) {
+    const answer = await ask('Apply these changes? [n] ');
+    return answer.toLowerCase().startsWith('y');
+  }
+  const answer = await ask(
+    'This deletes ' +
+      deletes.length +
+      ' records. Type ' +
+      domain.name +
+      ' to apply the changes: '
+  );
+  return answer == domain.name;
+}
+
+// Save what sync would change, to review before apply makes the changes
+async function savePlan(domain, client) {
+  const zoneClient = domain.client ? domain.client : client;
+  const existing = await getRecords(zoneClient, domain);
+  const changes = planChanges(
+    snapshotRecords,
+    existing,
+    domain.name,
+    options.prune
+  );
+  if (changes.length == 0) {
+    log.success('Nothing to change in ' + domain.name);
+    return;
+  }
+  printPlan(domain, changes);
+  const file = options.out ? options.out : domain.name + '.plan.json';
+  const plan = planDocument(domain, args[1], changes, existing);
+  fs.writeFileSync(file, JSON.stringify(plan, null, 2) + '\n');
+  log.success('Saved the plan to ' + file + ', run apply ' + file);
+}
+
+// Make exactly the changes of a saved plan, as long as the zone is still
+// as it was when the plan was made
+async function applyPlan(domain, client) {
+  const zoneClient = domain.client ? domain.client : client;
+  if (domain.id != savedPlan.zone_id) {
+    log.error(
+      'The plan is for zone ID ' +
+        savedPlan.zone_id +
+        ', ' +
+        domain.name +
+        ' is now ' +
+        domain.id
+    );
+    process.exit(EXIT_CODES.FAILURE);
+  }
+  const existing = await getRecords(zoneClient, domain);
+  if (recordsDigest(existing) != savedPlan.records_digest) {
+    log.error(
+      domain.name +
+        ' changed since the plan was made at ' +
+        savedPlan.created_at +
+        ', run plan again'
+    );
+    process.exit(EXIT_CODES.FAILURE);
+  }
+  printPlan(domain, savedPlan.changes);
+  if (await applyChanges(domain, zoneClient, savedPlan.changes)) {
+    log.success(
+      'Applied ' + savedPlan.changes.length + ' changes to ' + domain.name
+    );
+  }
+}
+
+// Copy a zone's records from the account of one profile to another's,
+// adding the zone there first if it is not there yet
+async function migrateZone(name) {
+  const source = createClient(
+    await zoneCredentials(name, { profile: options.fromProfile })
+  );
+  const target = createClient(
+    await zoneCredentials(name, { profile: options.toProfile })
+  );
+  const found = await getDomain(source, name);
+  if (found.length == 0) {
+    log.error(name + ' not found with profile ' + options.fromProfile);
+    process.exit(EXIT_CODES.NOTHING_TO_DO);
+  }
+  const payloads = copyRecords(await getRecords(source, found[0]), name);
+  let domain = (await getDomain(target, name))[0];
+  const existing = domain ? await getRecords(target, domain) : [];
+  const changes = planChanges(payloads, existing, name, false);
+
+  if (!domain) {
+    log.info(name + ' will be added to the account of ' + options.toProfile);
+  } else if (changes.length == 0) {
+    log.success(name + ' already has every record in ' + options.toProfile);
+    return;
+  }
+  printPlan({ name: name }, changes);
+  if (options.dryRun) return;
+  if (!options.yes && !(await confirmChanges({ name: name }, changes))) {
+    log.info('Nothing was changed.');
+    return;
+  }
+
+  if (!domain) {
+    const accountId = await targetAccount(target);
+    const zone = await createZone(target, name, accountId);
+    domain = addDomainsToList({ result: [zone] }, [])[0];
+    log.info('Added ' + name + ' to account ' + accountId);
+  }
+  if (await applyChanges(domain, target, changes)) {
+    log.success(
+      'Copied ' +
+        changes.length +
+        ' records to ' +
+        name +
+        ' with profile ' +
+        options.toProfile
+    );
+  }
+  if (domain.status == 'pending') {
+    log.info(
+      'Point the registrar at ' +
+        domain.nameServers.join(', ') +
+        ' to finish moving ' +
+        name
+    );
+  }
+}
+
+// The account new zones go in, from --to-account when the credentials
+// reach more than one
+async function targetAccount(client) {
+  if (options.toAccount) return options.toAccount;
+  const response = await client.get('accounts');
+  const accounts = response.data.result;
+  if (accounts.length != 1) {
+    throw new Error(
+      'The ' +
+        options.toProfile +
+        ' credentials reach ' +
+        accounts.length +
+        ' accounts, choose one with --to-account'
+    );
+  }
+  return accounts[0].id;
+}
+
+// Colors of the plan diff lines, as git diff uses them
+const DIFF_COLORS = { '-': 'red', '+': 'green', '@': 'cyan' };
+
+// Where the records of a plan come from, to name it in the diff
+function planSource() {
+  if (command == 'apply') return savedPlan.snapshot;
+  if (command == 'migrate') return 'profile ' + options.fromProfile;
+  if (command == 'clone') return args[0];
+  return args[1];
+}
+
+function printPlan(domain, changes, title) {
+  log.info((title ? title : 'Plan for ' + domain.name) + ':\n');
+  planDiff(changes, domain.name, planSource()).forEach((line) =>
+    log.info(line.text, DIFF_COLORS[line.sign])
+  );
+  const count = (action) =>
+    changes.filter((change) => change.action == action).length;
+  log.info(
+    '\n' +
+      count('create') +
+      ' to add, ' +
+      count('update') +
+      ' to change, ' +
+      count('delete') +
+      ' to delete.'
+  );
+}
+
+// Apply the changes, carrying on past any the API rejects. Deletes, updates
+// and creates each finish before the next start, with --concurrency writes
+// at a time and no more than --write-rate a second. Returns whether all of
+// them were applied.
+async function applyChanges(domain, client, changes) {
+  const throttle = createThrottle(
+    options.writeRate ? options.writeRate : DEFAULT_WRITE_RATE
+  );
+  const errors = [];
+  let applied = 0;
+  // Ctrl-C lets the writes in flight finish, a second one stops at once
+  let interrupted = false;
+  const interrupt = () => (interrupted = true);
+  process.once('SIGINT', interrupt);
+  const bar =
+    process.stdout.isTTY && !options.quiet && changes.length > 1
+      ? createProgress(changes.length, process.stdout)
+      : null;
+  log.setProgress(bar);
+
+  for (const action of ['delete', 'update', 'create']) {
+    const batch = changes.filter((change) => change.action == action);
+    await runConcurrently(batch, concurrency(), async (change) => {
+      if (interrupted) return;
+      if (bar) bar.start(describeChange(change));
+      try {
+        await throttledChange(client, domain, change, throttle, (seconds) =>
+          log.verbose('Rate limited, waiting ' + seconds + 's')
+        );
+        applied++;
+        log.verbose(describeChange(change));
+      } catch (error) {
+        errors.push(error);
+        log.error(
+          describeChange(change) + ': ' + apiErrorMessages(error).join(', ')
         );
+      } finally {
+        if (bar) bar.tick();
       }
-    })
-    .catch(function (error) {
-      // ERROR HANDLING
-      error.response.data.errors.forEach((error) => {
-        console.error('Error:' + error.message);
-      });
     });
+  }
+  process.removeListener('SIGINT', interrupt);
+  if (bar) bar.clear();
+  log.setProgress(null);
+
+  if (interrupted) {
+    log.warn(
+      'Stopped after ' +
+        applied +
+        ' of ' +
+        changes.length +
+        ' changes, run ' +
+        (command == 'apply' ? 'plan' : command) +
+        ' again to carry on'
+    );
+    process.exitCode = EXIT_CODES.PARTIAL;
+    return false;
+  }
+  if (errors.length) {
+    log.error(errors.length + ' of ' + changes.length + ' changes failed');
+    process.exitCode = applied ? EXIT_CODES.PARTIAL : exitCodeFor(errors[0]);
+  }
+  return errors.length == 0;
+}
+
+// Query parameters for zone listings, filtered server side where possible
+function zoneParams(params) {
+  if (options.accountId) {
+    params['account.id'] = options.accountId;
+  }
+  return params;
+}
+
+async function getDomains(client) {
+  const domainList = [];
+  let page = 1;
+  let pageInfo;
+
+  do {
+    const response = await client.get('zones', {
+      params: zoneParams({ page: page }),
+    });
+    // Process Domain list
+    if (!response.data.success) {
+      throw new Error(
+        response.data.errors.map((error) => error.message).join(', ')
+      );
+    }
+    pageInfo = response.data.result_info;
+
+    log.verbose('Fetching batch of ' + pageInfo.count + ' DNS records ...');
+    addDomainsToList(response.data, domainList);
+
+    // Check for more pages in domain name list
+    page = pageInfo.page + 1;
+  } while (pageInfo.page < pageInfo.total_pages);
+
+  log.info('Fetchied ' + pageInfo.total_count + ' domains.');
+  return domainList;
+}
+
+// Look up a single zone by name instead of listing every zone
+async function getDomain(client, name) {
+  const response = await client.get('zones', {
+    params: zoneParams({ name: name }),
+  });
+  if (!response.data.success) {
+    throw new Error(
+      response.data.errors.map((error) => error.message).join(', ')
+    );
+  }
+  return addDomainsToList(response.data, []);
+}
+
+function selectDomains(domains) {
+  let selected = domains;
+  // A zone named on the command line is exported whatever its state
+  if (!zoneArgument) {
+    selected = skipZones(selected, 'paused', options.includePaused, (domain) =>
+      Boolean(domain.paused)
+    );
+    selected = skipZones(
+      selected,
+      'pending',
+      options.includePending || options.status == 'pending',
+      (domain) => domain.status == 'pending'
+    );
+  }
+  if (options.status && options.status != 'all') {
+    selected = selected.filter((domain) => domain.status == options.status);
+  }
+  if (options.plan) {
+    selected = selected.filter((domain) => domain.planId == options.plan);
+  }
+  if (options.zones) {
+    const patterns = parseList(options.zones);
+    selected = selected.filter((domain) => matchesAny(domain.name, patterns));
+  }
+  if (options.zonesRegex) {
+    const pattern = new RegExp(options.zonesRegex, 'i');
+    selected = selected.filter((domain) => pattern.test(domain.name));
+  }
+  if (options.excludeZones) {
+    const patterns = parseList(options.excludeZones);
+    selected = selected.filter((domain) => !matchesAny(domain.name, patterns));
+  }
+
+  if (selected.length < domains.length) {
+    log.info(
+      'Selected ' + selected.length + ' of ' + domains.length + ' domains.'
+    );
+  }
+  return selected;
+}
+
+// Leave out zones in an edge state unless their --include-<state> flag is set
+function skipZones(domains, state, included, inState) {
+  if (included) return domains;
+  const kept = domains.filter((domain) => !inState(domain));
+  const skipped = domains.length - kept.length;
+  if (skipped) {
+    log.info(
+      'Skipping ' +
+        skipped +
+        ' ' +
+        state +
+        ' zone' +
+        (skipped == 1 ? '' : 's') +
+        ', pass --include-' +
+        state +
+        ' to include ' +
+        (skipped == 1 ? 'it' : 'them')
+    );
+  }
+  return kept;
+}
+
+async function addOverrideDomains(domains) {
+  const overrides = config.data.zones ? config.data.zones : {};
+
+  for (const name of Object.keys(overrides)) {
+    if (zoneArgument && name != zoneArgument) continue;
+    const client = createClient(await zoneCredentials(name, overrides[name]));
+    const existing = domains.find((domain) => domain.name == name);
+    if (existing) {
+      existing.client = client;
+      continue;
+    }
+
+    // Narrowly scoped tokens may be the only ones able to see their zone
+    const response = await client.get('zones', {
+      params: zoneParams({ name: name }),
+    });
+    if (response.data.result.length == 0 && options.accountId) {
+      log.verbose('Skipping ' + name + ', it is not in the selected account');
+      continue;
+    } else if (response.data.result.length == 0) {
+      log.error(
+        'Zone ' + name + ' not visible with its configured credentials'
+      );
+      continue;
+    }
+    log.verbose('Adding ' + name + ' using its own credentials');
+    addDomainsToList(response.data, domains);
+    domains[domains.length - 1].client = client;
+  }
 }
 
-function exportDNS(domain) {
-  // Check if directory exists and then create it if it doesn't
-  if (!fs.existsSync('./domains')) {
-    fs.mkdirSync('./domains');
-  }
-
-  // Get domain records
-  axios
-    .get('zones/' + domain.id + '/dns_records/export', {
-      baseURL: CLOUDFLARE_ENDPOINT,
-      headers: {
-        'X-Auth-Email': process.env.CLOUDFLARE_USER_EMAIL,
-        'X-Auth-Key': process.env.CLOUDFLARE_API_KEY,
-        'Content-Type': 'application/json',
-      },
-    })
-    .then(function (response) {
-      // Write Domain Files
-      fs.writeFile(`./domains/${domain.name}.txt`, response.data, (err) => {
-        if (err) console.log('Error writing file: ' + err);
-      });
-    })
-    .catch(function (error) {
-      // ERROR HANDLING
-      error.response.data.errors.forEach((error) => {
-        console.error('Error:' + error.message);
-      });
+async function zoneCredentials(name, override) {
+  const credentials = {};
+  const profiles = config.data.profiles ? config.data.profiles : {};
+  if (override.profile && !profiles[override.profile]) {
+    throw new Error(
+      'Profile "' + override.profile + '" for zone ' + name + ' not found'
+    );
+  }
+
+  // Start from the referenced profile, then apply keys set on the zone itself
+  [profiles[override.profile], override].forEach((entry) => {
+    Object.keys(entry ? entry : {}).forEach((key) => {
+      if (PROFILE_VARIABLES[key]) {
+        credentials[PROFILE_VARIABLES[key]] = String(entry[key]);
+      }
     });
+  });
+  if (
+    credentials.CLOUDFLARE_API_TOKEN_FILE &&
+    !credentials.CLOUDFLARE_API_TOKEN
+  ) {
+    credentials.CLOUDFLARE_API_TOKEN = readTokenFile(
+      credentials.CLOUDFLARE_API_TOKEN_FILE
+    );
+  }
+  await readSecretBackends(credentials);
+
+  if (!authMode(credentials)) {
+    throw new Error('No credentials configured for zone ' + name);
+  }
+  // These never reach process.env, so tell the redaction layer about them
+  keyring.CREDENTIAL_VARIABLES.forEach((variable) => {
+    if (variable != 'CLOUDFLARE_USER_EMAIL') {
+      registerSecret(credentials[variable]);
+    }
+  });
+  return credentials;
 }
 
-function addDomainsToList(domains) {
+function currentFormat() {
+  return FORMATS[options.format ? options.format : 'bind'];
+}
+
+function outputDir() {
+  if (options.output) return options.output;
+  return options.localOutput ? './domains' : defaultOutputDir();
+}
+
+function toStdout() {
+  return options.singleFile
+    ? options.singleFile == '-'
+    : options.output == '-';
+}
+
+// The zone's file, or with --split-by-type its directory
+function outputPath(domain) {
+  if (options.splitByType) return `${outputDir()}/${domain.name}`;
+  return compressedName(
+    `${outputDir()}/${domain.name}.${currentFormat().extension}`
+  );
+}
+
+// One part per record type with --split-by-type, else the whole zone
+function zoneParts(zone) {
+  if (!options.splitByType) return [{ file: null, zone: zone }];
+  const texts = zone.text ? splitZoneText(zone.text, zone.name) : {};
+  const types = [];
+  zone.records.forEach((record) => {
+    if (!types.includes(record.type)) types.push(record.type);
+  });
+  return types.sort().map((type) => ({
+    file: type + '.' + currentFormat().extension,
+    zone: Object.assign({}, zone, {
+      text: texts[type],
+      records: zone.records.filter((record) => record.type == type),
+    }),
+  }));
+}
+
+function reportDryRun(domains) {
+  log.info('Dry run, nothing will be written.\n');
+  let overwrites = 0;
+  if (toStdout()) {
+    domains.forEach((domain) => log.info('  ' + domain.name + ' -> stdout'));
+    log.info('\nWould export ' + domains.length + ' domains to stdout.');
+    return;
+  } else if (options.singleFile || options.archive) {
+    const file = compressedName(
+      options.singleFile
+        ? options.singleFile
+        : path.join(outputDir(), archiveName(options.archive, new Date()))
+    );
+    domains.forEach((domain) => log.info('  ' + domain.name + ' -> ' + file));
+    log.info(
+      '\nWould export ' +
+        domains.length +
+        ' domains to ' +
+        file +
+        (fs.existsSync(file) ? ', overwriting it.' : '.')
+    );
+    return;
+  }
+  domains.forEach((domain) => {
+    const file = outputPath(domain);
+    const exists = fs.existsSync(file);
+    if (exists) overwrites++;
+    log.info(
+      '  ' + domain.name + ' -> ' + file + (exists ? ' (overwrite)' : ' (new)')
+    );
+  });
+  log.info(
+    '\nWould export ' +
+      domains.length +
+      ' domains, overwriting ' +
+      overwrites +
+      ' existing files.'
+  );
+}
+
+// Get domain records in the form the format renders from
+async function fetchZone(domain, client) {
+  const format = currentFormat();
+  const zone = {
+    id: domain.id,
+    name: domain.name,
+    status: domain.status,
+    plan: domain.planId,
+    account: domain.account,
+    account_id: domain.accountId,
+  };
+  if (format.source == 'records') {
+    zone.records = await getRecords(client, domain);
+  } else {
+    const response = await client.get(
+      'zones/' + domain.id + '/dns_records/export'
+    );
+    zone.text = response.data;
+    zone.records = parseZone(response.data, domain.name).records;
+  }
+  if (options.stripComments && zone.text) {
+    zone.text = stripComments(zone.text);
+  }
+  if (options.canonicalize) {
+    if (zone.text) zone.text = canonicalZone(zone.text, domain.name);
+    zone.records.sort(compareRecords);
+  }
+  // Proxied status and the like only come from the records API
+  if (options.annotate && zone.text) {
+    const records = await getRecords(client, domain);
+    zone.text = annotateZone(zone.text, domain.name, records);
+  }
+  const extras = await fetchExtras(client, domain, options);
+  zone.extras = extras.files;
+  zone.extraErrors = extras.failed.map(
+    (failed) => failed.name + ': ' + apiErrorMessages(failed.error).join(', ')
+  );
+  // Stdout has no room for a second file per zone
+  if (!options.noMeta && !toStdout()) {
+    zone.extras.unshift(zoneMeta(domain, zone));
+  }
+  return zone;
+}
+
+// What the zone listing says about the zone, kept as meta.json
+function zoneMeta(domain, zone) {
+  const meta = {
+    id: domain.id,
+    name: domain.name,
+    account: domain.account,
+    account_id: domain.accountId,
+    plan: domain.plan,
+    plan_id: domain.planId,
+    status: domain.status,
+    paused: domain.paused,
+    created_on: domain.createdOn,
+    modified_on: domain.modifiedOn,
+    name_servers: domain.nameServers,
+    // The SOA of BIND exports is not a record in Cloudflare
+    record_count: zone.records.filter((record) => record.type != 'SOA').length,
+  };
+  return { file: 'meta.json', contents: JSON.stringify(meta, null, 2) + '\n' };
+}
+
+// What every format is rendered with, the same for all zones of the run
+function renderOptions() {
+  return { exportedAt: runStarted, hostsSubdomain: options.hostsSubdomain };
+}
+
+// The --header-template block for a zone, or for --single-file the run
+function fileHeader(domain) {
+  if (!headerTemplate) return '';
+  return renderHeader(
+    headerTemplate,
+    {
+      zone: domain ? domain.name : null,
+      zone_id: domain ? domain.id : null,
+      account: domain ? domain.account : null,
+      account_id: domain ? domain.accountId : null,
+      exported_at: headerTimestamp(runStarted),
+      format: options.format ? options.format : 'bind',
+    },
+    currentFormat().comment
+  );
+}
+
+function writeZone(domain, zone, separate, writer) {
+  const format = currentFormat();
+  const parts = zoneParts(zone);
+  const written = { file: '-', bytes: 0 };
+  parts.forEach((part) => {
+    const contents =
+      fileHeader(domain) + format.render(part.zone, renderOptions());
+    written.bytes += Buffer.byteLength(contents);
+    if (!writer) {
+      writeToStdout(domain, contents, separate, part.file);
+      return;
+    }
+
+    // Write Domain Files
+    const name = part.file
+      ? domain.name + '/' + part.file
+      : domain.name + '.' + format.extension;
+    try {
+      const file = writer.write(name, contents);
+      written.file = part.file ? path.dirname(file) : file;
+      log.verbose('Exported ' + domain.name + ' to ' + file);
+    } catch (err) {
+      throw new Error('Writing file: ' + err.message);
+    }
+  });
+  writeExtras(domain, zone, writer);
+  return written;
+}
+
+// The --include-* files, in a directory named after the zone
+function writeExtras(domain, zone, writer) {
+  zone.extras.forEach((extra) => {
+    if (!writer) {
+      writeToStdout(domain, extra.contents, true, extra.file);
+      return;
+    }
+    try {
+      const file = writer.write(domain.name + '/' + extra.file, extra.contents);
+      log.verbose('Exported ' + domain.name + ' ' + extra.file + ' to ' + file);
+    } catch (err) {
+      throw new Error('Writing file: ' + err.message);
+    }
+  });
+}
+
+async function getRecords(client, domain) {
+  const records = [];
+  let page = 1;
+  let pageInfo;
+
+  do {
+    const response = await client.get('zones/' + domain.id + '/dns_records', {
+      params: { page: page, per_page: 100 },
+    });
+    if (!response.data.success) {
+      throw new Error(
+        response.data.errors.map((error) => error.message).join(', ')
+      );
+    }
+    pageInfo = response.data.result_info;
+    records.push(...response.data.result);
+    page = pageInfo.page + 1;
+  } while (pageInfo.page < pageInfo.total_pages);
+
+  return records;
+}
+
+function writeToStdout(domain, contents, separate, part) {
+  const body = contents.endsWith('\n') ? contents : contents + '\n';
+  const file = part
+    ? domain.name + '/' + part
+    : domain.name + '.' + currentFormat().extension;
+  process.stdout.write(
+    separate || part ? '==> ' + file + ' <==\n' + body + '\n' : body
+  );
+}
+
+function addDomainsToList(domains, domainList) {
   domains.result.forEach((domain) => {
     // console.log('Domain: ' + domain.name + '   ID: ' + domain.id);
-    domainList.push({ id: domain.id, name: domain.name });
+    domainList.push({
+      id: domain.id,
+      name: domain.name,
+      status: domain.status,
+      plan: domain.plan ? domain.plan.name : null,
+      // free, pro, business or enterprise
+      planId: domain.plan ? domain.plan.legacy_id : null,
+      paused: domain.paused,
+      account: domain.account ? domain.account.name : null,
+      accountId: domain.account ? domain.account.id : null,
+      nameServers: domain.name_servers ? domain.name_servers : [],
+      createdOn: domain.created_on,
+      modifiedOn: domain.modified_on,
+    });
   });
 
   return domainList;
 }
 
-function checkEnvironment() {
-  // .env file exists, load into ENV
-  if (fs.existsSync('.env')) {
-    readEnvFile('.env')
-  } else if(fs.existsSync(args[0])) {
-    console.log('Using custom ENV file: ' + args[0]);
-    readEnvFile(args[0])
-  } else {
-    console.log('No environment ( .env ) file found. Exiting');
-    process.exit(1);
+async function checkEnvironment() {
+  loadEnvFile();
+  applyEnvironmentAliases();
+  applyProfile();
+  applyCredentialFlags();
+  if (process.env.CLOUDFLARE_API_ENDPOINT) {
+    log.verbose('Using API endpoint: ' + process.env.CLOUDFLARE_API_ENDPOINT);
+  }
+  await readSecretBackends(process.env);
+
+  // Only fall back to the keyring, then asking, when nothing else matched
+  if (!authMode()) {
+    if (readKeyring()) {
+      log.verbose('Using credentials from the OS keyring');
+    } else if (process.stdin.isTTY && process.stdout.isTTY) {
+      log.info('No credentials found in the environment or a .env file.');
+      await promptForCredentials();
+    }
+  }
+  validateEnvironment();
+}
+
+async function readSecretBackends(env) {
+  try {
+    await resolveSecrets(env);
+  } catch (error) {
+    log.error(error.message);
+    process.exit(EXIT_CODES.CONFIG);
+  }
+}
+
+async function resolveSecrets(env) {
+  // Only consult Vault when nothing more specific supplied a token
+  if (env.CLOUDFLARE_VAULT_PATH && !env.CLOUDFLARE_API_TOKEN) {
+    log.verbose('Using API token from Vault: ' + env.CLOUDFLARE_VAULT_PATH);
+    const kvVersion = options.vaultKvVersion
+      ? { CLOUDFLARE_VAULT_KV_VERSION: options.vaultKvVersion }
+      : {};
+    env.CLOUDFLARE_API_TOKEN = await readVaultToken(
+      Object.assign({}, process.env, env, kvVersion)
+    );
+  }
+
+  // Swap secret references for the values they point at
+  for (const name of keyring.CREDENTIAL_VARIABLES) {
+    if (isAwsReference(env[name])) {
+      env[name] = await readAwsSecret(env[name], process.env);
+    } else if (isOnePasswordReference(env[name])) {
+      env[name] = readOnePasswordSecret(env[name]);
+    }
+  }
+}
+
+function parseCommandLine() {
+  try {
+    return parseArgs(process.argv.slice(2));
+  } catch (error) {
+    log.error(error.message);
+    process.exit(EXIT_CODES.CONFIG);
+  }
+}
+
+function configureRequests() {
+  // Env files don't override variables already set, so the flag wins
+  if (options.apiEndpoint) {
+    process.env.CLOUDFLARE_API_ENDPOINT = options.apiEndpoint;
+  }
+  if (options.insecure) {
+    log.warn(
+      'TLS certificate checks are disabled by --insecure, anyone between you and Cloudflare can read and change the traffic, including your credentials'
+    );
+  }
+  try {
+    configureHttp({
+      proxy: options.proxy,
+      timeout: options.timeout,
+      connectTimeout: options.connectTimeout,
+      readTimeout: options.readTimeout,
+      cacert: options.cacert,
+      insecure: options.insecure,
+    });
+  } catch (error) {
+    log.error(error.message);
+    process.exit(EXIT_CODES.CONFIG);
+  }
+}
+
+function readConfig() {
+  // init writes the config file, so there may not be one yet
+  if (command == 'init') return { path: null, data: {} };
+  try {
+    return loadConfig(options.config);
+  } catch (error) {
+    // Reported as one of the checks instead
+    if (command == 'doctor') return { path: null, data: {}, error: error };
+    log.error(error.message);
+    process.exit(EXIT_CODES.CONFIG);
+  }
+}
+
+function selectedProfile() {
+  return options.profile ? options.profile : config.data.default_profile;
+}
+
+function applyProfile() {
+  const name = selectedProfile();
+  if (!name) return;
+  log.verbose('Using profile: ' + name);
+
+  const profiles = config.data.profiles ? config.data.profiles : {};
+  const profile = profiles[name];
+  if (!profile) {
+    log.error(
+      'Profile "' +
+        name +
+        '" not found in ' +
+        (config.path ? config.path : 'any config file')
+    );
+    process.exit(EXIT_CODES.CONFIG);
+  }
+
+  // A profile with its own key replaces any token from the environment
+  if ((profile.api_key || profile.email) && !profile.api_token) {
+    delete process.env.CLOUDFLARE_API_TOKEN;
+  }
+  Object.keys(profile).forEach((key) => {
+    if (PROFILE_VARIABLES[key]) {
+      process.env[PROFILE_VARIABLES[key]] = String(profile[key]);
+    }
+  });
+}
+
+// Profile and [defaults] keys named after flags set those flags' defaults
+function applyOptionDefaults() {
+  const profiles = config.data.profiles ? config.data.profiles : {};
+  const tables = [
+    ['profile ' + selectedProfile(), profiles[selectedProfile()]],
+    ['[defaults]', config.data.defaults],
+  ];
+
+  tables.forEach((table) => {
+    const values = table[1] ? table[1] : {};
+    Object.keys(values).forEach((key) => {
+      const flag = key.replace(/_/g, '-');
+      if (PROFILE_VARIABLES[key] && table[0] != '[defaults]') {
+        return;
+      } else if (!OPTIONS[flag]) {
+        log.error('Unknown key "' + key + '" in ' + table[0]);
+        process.exit(EXIT_CODES.CONFIG);
+      }
+
+      try {
+        const option = camelCase(flag);
+        if (options[option] === undefined) {
+          options[option] = configValue(flag, values[key]);
+        }
+      } catch (error) {
+        log.error(config.path + ': ' + error.message);
+        process.exit(EXIT_CODES.CONFIG);
+      }
+    });
+  });
+
+  ['split-by-type', 'archive'].forEach((flag) => {
+    if (options.singleFile && options[camelCase(flag)]) {
+      log.error('--single-file and --' + flag + ' cannot be used together');
+      process.exit(EXIT_CODES.CONFIG);
+    }
+  });
+  if (options.compressLevel && !options.compress) {
+    log.error('--compress-level needs --compress');
+    process.exit(EXIT_CODES.CONFIG);
+  } else if (options.compressLevel) {
+    const levels = CODECS[options.compress].levels;
+    if (options.compressLevel > levels.max) {
+      log.error(
+        options.compress +
+          ' compression levels go from ' +
+          levels.min +
+          ' to ' +
+          levels.max
+      );
+      process.exit(EXIT_CODES.CONFIG);
+    }
+  }
+  const formatName = options.format ? options.format : 'bind';
+  if (FORMATS[formatName].combined && !options.singleFile) {
+    ['split-by-type', 'archive'].forEach((flag) => {
+      if (options[camelCase(flag)]) {
+        log.error(
+          'The ' + formatName + ' format cannot be used with --' + flag
+        );
+        process.exit(EXIT_CODES.CONFIG);
+      }
+    });
+    const file = 'cloudflare-dns.' + FORMATS[formatName].extension;
+    options.singleFile =
+      options.output == '-' ? '-' : path.join(outputDir(), file);
+  }
+
+  if (formatName == 'template' && !options.template) {
+    log.error('--format template needs --template');
+    process.exit(EXIT_CODES.CONFIG);
+  } else if (formatName == 'template') {
+    try {
+      FORMATS.template.use(options.template);
+    } catch (error) {
+      log.error(error.message);
+      process.exit(EXIT_CODES.CONFIG);
+    }
+  } else if (options.template) {
+    log.error('--template needs --format template');
+    process.exit(EXIT_CODES.CONFIG);
+  }
+
+  if (command == 'audit') {
+    ['since', 'until'].forEach((flag) => {
+      if (!options[flag]) return;
+      try {
+        parseTime(options[flag], new Date());
+      } catch (error) {
+        log.error('--' + flag + ': ' + error.message);
+        process.exit(EXIT_CODES.CONFIG);
+      }
+    });
+  }
+  if (SNAPSHOT_COMMANDS.includes(command)) {
+    if (!args[1]) {
+      log.error(command + ' needs a zone and a file to read the records from');
+      process.exit(EXIT_CODES.CONFIG);
+    }
+    try {
+      const text = fs.readFileSync(args[1], 'utf8');
+      snapshotRecords = readSnapshot(text, args[1], args[0]);
+    } catch (error) {
+      log.error(args[1] + ': ' + error.message);
+      process.exit(EXIT_CODES.CONFIG);
+    }
+  }
+  if (command == 'clone') {
+    if (!args[1]) {
+      log.error('clone needs the zone to copy and the zone to copy it to');
+      process.exit(EXIT_CODES.CONFIG);
+    }
+    try {
+      if (options.rewrite) parseRewrites(parseList(options.rewrite));
+    } catch (error) {
+      log.error('--rewrite: ' + error.message);
+      process.exit(EXIT_CODES.CONFIG);
+    }
+  }
+  if (command == 'migrate') {
+    if (!args[0] || !options.fromProfile || !options.toProfile) {
+      log.error('migrate needs a zone, --from-profile and --to-profile');
+      process.exit(EXIT_CODES.CONFIG);
+    } else if (options.fromProfile == options.toProfile) {
+      log.error('--from-profile and --to-profile are the same');
+      process.exit(EXIT_CODES.CONFIG);
+    }
+    [options.fromProfile, options.toProfile].forEach((name) => {
+      if (!profiles[name]) {
+        log.error('Profile "' + name + '" not found in the config file');
+        process.exit(EXIT_CODES.CONFIG);
+      }
+    });
+  }
+  if (command == 'apply') {
+    if (!args[0]) {
+      log.error('apply needs the plan file written by plan');
+      process.exit(EXIT_CODES.CONFIG);
+    }
+    try {
+      savedPlan = readPlan(fs.readFileSync(args[0], 'utf8'));
+    } catch (error) {
+      log.error(args[0] + ': ' + error.message);
+      process.exit(EXIT_CODES.CONFIG);
+    }
+    zoneArgument = savedPlan.zone;
+  }
+  if (options.includeAccounts && toStdout() && !options.archive) {
+    log.error('--include-accounts cannot write to stdout without --archive');
+    process.exit(EXIT_CODES.CONFIG);
+  }
+  if (options.hostsSubdomain && formatName != 'hosts') {
+    log.error('--hosts-subdomain needs --format hosts');
+    process.exit(EXIT_CODES.CONFIG);
+  }
+
+  if (options.annotate && FORMATS[formatName].source == 'records') {
+    log.error(
+      'The ' + formatName + ' format already has every record field, ' +
+        'leave out --annotate'
+    );
+    process.exit(EXIT_CODES.CONFIG);
+  }
+
+  if (FORMATS[formatName].index && options.splitByType) {
+    log.error(
+      'The ' + formatName + ' format cannot be used with --split-by-type'
+    );
+    process.exit(EXIT_CODES.CONFIG);
+  }
+
+  if (options.headerTemplate) {
+    if (!FORMATS[formatName].comment) {
+      log.error(
+        '--header-template is not supported by the ' + formatName + ' format'
+      );
+      process.exit(EXIT_CODES.CONFIG);
+    }
+    try {
+      headerTemplate = loadHeaderTemplate(options.headerTemplate);
+    } catch (error) {
+      log.error(error.message);
+      process.exit(EXIT_CODES.CONFIG);
+    }
+  }
+  // Compressed files would run together on stdout
+  const combined = options.archive || options.singleFile;
+  if (options.compress && options.output == '-' && !combined) {
+    log.error('--compress needs --archive or --single-file with --output -');
+    process.exit(EXIT_CODES.CONFIG);
+  }
+  // The index links each page by name, which compressed files no longer have
+  if (options.compress && FORMATS[formatName].index && !combined) {
+    log.error(
+      'The ' + formatName + ' format needs --archive to use --compress'
+    );
+    process.exit(EXIT_CODES.CONFIG);
+  }
+}
+
+function loadEnvFile() {
+  // The env file is optional, variables may already be set in the environment
+  if (options.envFile) {
+    if (!fs.existsSync(options.envFile)) {
+      log.error('Env file not found: ' + options.envFile);
+      process.exit(EXIT_CODES.CONFIG);
+    }
+    log.verbose('Using custom ENV file: ' + options.envFile);
+    readEnvFile(options.envFile);
+  } else if (fs.existsSync('.env')) {
+    readEnvFile('.env');
+  } else if (legacyEnvFile) {
+    log.verbose('Using custom ENV file: ' + legacyEnvFile);
+    log.warn(
+      'Passing the env file as an argument is deprecated, use --env-file'
+    );
+    readEnvFile(legacyEnvFile);
   }
 }
 
 function readEnvFile(envFilename) {
   require('dotenv').config({ path: envFilename });
-  if (process.env.CLOUDFLARE_API_KEY && process.env.CLOUDFLARE_USER_EMAIL) {
+}
+
+function applyEnvironmentAliases() {
+  // The CLOUDFLARE_* names win when both are set
+  Object.keys(ENV_ALIASES).forEach((alias) => {
+    if (process.env[alias] && !process.env[ENV_ALIASES[alias]]) {
+      process.env[ENV_ALIASES[alias]] = process.env[alias];
+    }
+  });
+}
+
+function readTokenFile(filename) {
+  try {
+    return fs.readFileSync(filename, 'utf8').trim();
+  } catch (error) {
+    log.error('Unable to read API token from ' + filename);
+    process.exit(EXIT_CODES.CONFIG);
+  }
+}
+
+function applyCredentialFlags() {
+  // Flags take precedence over anything loaded from files or the keyring
+  if (options.apiToken) {
+    process.env.CLOUDFLARE_API_TOKEN = options.apiToken;
+  } else if (options.tokenStdin) {
+    // File descriptor 0 is stdin, e.g. `pass show cf | ... --token-stdin`
+    process.env.CLOUDFLARE_API_TOKEN = readTokenFile(0);
+  } else if (process.env.CLOUDFLARE_API_TOKEN_FILE) {
+    process.env.CLOUDFLARE_API_TOKEN = readTokenFile(
+      process.env.CLOUDFLARE_API_TOKEN_FILE
+    );
+  } else if (options.apiKey || options.email) {
+    // Explicit key flags mean key auth, even if a token is in the env file
+    delete process.env.CLOUDFLARE_API_TOKEN;
+  }
+  if (options.apiKey) {
+    process.env.CLOUDFLARE_API_KEY = options.apiKey;
+  }
+  if (options.email) {
+    process.env.CLOUDFLARE_USER_EMAIL = options.email;
+  }
+}
+
+function readKeyring() {
+  try {
+    return keyring.loadCredentials(process.env).length > 0;
+  } catch (error) {
+    // No keyring tool available on this machine
+    return false;
+  }
+}
+
+function manageCredentials(action) {
+  try {
+    if (action == 'store') {
+      // Credentials come from flags, the env file or the shell
+      loadEnvFile();
+      applyEnvironmentAliases();
+      applyProfile();
+      applyCredentialFlags();
+      validateEnvironment();
+
+      const stored = keyring.storeCredentials(process.env);
+      log.success('Stored ' + stored.join(', ') + ' in the OS keyring.');
+      log.info('The plaintext env file is no longer required.');
+    } else if (action == 'clear') {
+      keyring.clearCredentials();
+      log.success('Removed stored credentials from the OS keyring.');
+    } else {
+      process.stdout.write(helpText('credentials'));
+      process.exit(EXIT_CODES.CONFIG);
+    }
+  } catch (error) {
+    log.error(error.message);
+    process.exit(EXIT_CODES.FAILURE);
+  }
+}
+
+async function askCredentials() {
+  const token = await askHidden(
+    'Cloudflare API token (leave blank to use the global API key): '
+  );
+  if (token) {
+    process.env.CLOUDFLARE_API_TOKEN = token;
+  } else {
+    process.env.CLOUDFLARE_API_KEY = await askHidden('Cloudflare API key: ');
+    process.env.CLOUDFLARE_USER_EMAIL = await ask('Cloudflare account email: ');
+  }
+}
+
+async function promptForCredentials() {
+  await askCredentials();
+
+  const save = await ask(
+    'Save these credentials to the [k]eyring, a .[e]nv file or [n]owhere? [n] '
+  );
+  try {
+    if (save.toLowerCase().startsWith('k')) {
+      keyring.storeCredentials(process.env);
+      log.success('Saved credentials to the OS keyring.');
+    } else if (save.toLowerCase().startsWith('e')) {
+      writeEnvFile('.env');
+      log.success('Saved credentials to .env');
+    }
+  } catch (error) {
+    log.error(error.message);
+  }
+}
+
+async function initConfig() {
+  if (!process.stdin.isTTY || !process.stdout.isTTY) {
+    log.error('init asks questions, run it from a terminal');
+    process.exit(EXIT_CODES.CONFIG);
+  }
+  const configPath = options.config ? options.config : defaultConfigPaths()[1];
+  if (fs.existsSync(configPath)) {
+    const answer = await ask(configPath + ' already exists, replace it? [n] ');
+    if (!answer.toLowerCase().startsWith('y')) return;
+  }
+
+  // Ask until Cloudflare accepts the credentials
+  for (;;) {
+    keyring.CREDENTIAL_VARIABLES.forEach((name) => delete process.env[name]);
+    await askCredentials();
+    try {
+      await verifyCredentials(createClient());
+      log.success('Credentials work.');
+      break;
+    } catch (error) {
+      log.error(error.message);
+    }
+  }
+
+  const outputAnswer = await ask(
+    'Directory for exported zones [' + defaultOutputDir() + ']: '
+  );
+  let format;
+  do {
+    format = await ask(
+      'Default format (' + FORMAT_NAMES.join(', ') + ') [bind]: '
+    );
+    format = format ? format : 'bind';
+  } while (!FORMAT_NAMES.includes(format));
+  const save = await ask(
+    'Keep the credentials in the OS [k]eyring or the [c]onfig file? [k] '
+  );
+
+  const lines = ['# Written by ' + PROGRAM + ' init', '', '[defaults]'];
+  if (outputAnswer) lines.push('output = ' + JSON.stringify(outputAnswer));
+  lines.push('format = ' + JSON.stringify(format));
+  if (save.toLowerCase().startsWith('c')) {
+    lines.push('', '[profiles.default]');
+    ['api_token', 'api_key', 'email'].forEach((key) => {
+      const value = process.env[PROFILE_VARIABLES[key]];
+      if (value) lines.push(key + ' = ' + JSON.stringify(value));
+    });
+    lines.splice(2, 0, 'default_profile = "default"', '');
+  } else {
+    try {
+      keyring.storeCredentials(process.env);
+    } catch (error) {
+      log.error(error.message);
+      process.exit(EXIT_CODES.FAILURE);
+    }
+  }
+
+  // The file may hold credentials, so only the owner can read it
+  fs.mkdirSync(path.dirname(configPath), { recursive: true });
+  fs.writeFileSync(configPath, lines.join('\n') + '\n', { mode: 0o600 });
+  log.success('Wrote ' + configPath);
+}
+
+async function runDoctor() {
+  const results = [];
+  const check = async (name, test) => {
+    try {
+      const detail = await test();
+      results.push([name, detail === null ? 'skipped' : 'ok', detail]);
+    } catch (error) {
+      results.push([name, 'FAIL', apiErrorMessages(error).join(', ')]);
+    }
+  };
+  let client = null;
+  let serverDate = null;
+
+  await check('Config file', () => {
+    if (config.error) throw config.error;
+    return config.path ? config.path : 'none found, using defaults';
+  });
+  await check('Credentials', async () => {
+    loadEnvFile();
+    applyEnvironmentAliases();
+    applyProfile();
+    applyCredentialFlags();
+    await resolveSecrets(process.env);
+    if (!authMode()) readKeyring();
+    if (!authMode()) {
+      throw new Error('none found in flags, the environment, .env or keyring');
+    }
+    client = createClient();
+    return AUTH_MODE_LABELS[authMode()];
+  });
+  await check('API reachable', async () => {
+    const probe = client ? client : createClient({});
+    let response;
+    try {
+      response = await probe.get('user/tokens/verify');
+    } catch (error) {
+      // Any HTTP answer, even a refusal, means the API can be reached
+      if (!error.response) throw error;
+      response = error.response;
+    }
+    serverDate = response.headers ? response.headers.date : null;
+    return apiEndpoint();
+  });
+  await check('Permissions', async () => {
+    if (!client) return null;
+    await verifyCredentials(client);
+    return 'can read zones and DNS records';
+  });
+  await check('Output directory', () => {
+    if (toStdout()) return null;
+    const dir = outputDir();
+    const probe = path.join(dir, '.doctor-' + process.pid);
+    fs.mkdirSync(dir, { recursive: true });
+    fs.writeFileSync(probe, '');
+    fs.unlinkSync(probe);
+    return dir + ' is writable';
+  });
+  await check('Clock', () => {
+    if (!serverDate) return null;
+    const skew = Math.round(
+      Math.abs(Date.now() - Date.parse(serverDate)) / 1000
+    );
+    if (skew > MAX_CLOCK_SKEW) {
+      throw new Error('local clock is ' + skew + 's away from Cloudflare');
+    }
+    return 'within ' + skew + 's of Cloudflare';
+  });
+
+  process.stdout.write(renderTable(['CHECK', 'RESULT', 'DETAILS'], results));
+  const failed = results.filter((result) => result[1] == 'FAIL').length;
+  if (failed) {
+    log.error(failed + ' of ' + results.length + ' checks failed');
+    process.exitCode = EXIT_CODES.FAILURE;
+  } else {
+    log.success('All checks passed.');
+  }
+}
+
+function writeEnvFile(envFilename) {
+  const lines = keyring.CREDENTIAL_VARIABLES.filter(
+    (name) => process.env[name]
+  ).map((name) => name + '=' + process.env[name]);
+
+  // Only the owner should be able to read saved secrets
+  fs.writeFileSync(envFilename, lines.join('\n') + '\n', { mode: 0o600 });
+}
+
+function validateEnvironment() {
+  const mode = authMode();
+  if (mode) {
+    if (
+      (mode == 'token' && process.env.CLOUDFLARE_API_TOKEN == 'NULL') ||
+      (mode == 'key' &&
+        process.env.CLOUDFLARE_API_KEY == 'NULL' &&
+        process.env.CLOUDFLARE_USER_EMAIL == 'NULL')
+    ) {
+      log.warn('Please enter you own API and EMAIL in the .env file\n\n');
+    }
+
     if (
-      process.env.CLOUDFLARE_API_KEY == 'NULL' &&
-      process.env.CLOUDFLARE_USER_EMAIL == 'NULL'
+      mode == 'service-key' &&
+      !isValidServiceKey(process.env.CLOUDFLARE_USER_SERVICE_KEY)
     ) {
-      console.info('Please enter you own API and EMAIL in the .env file\n\n');
+      log.error(
+        'CLOUDFLARE_USER_SERVICE_KEY is not a valid user service key (expected v1.0-...)'
+      );
+      process.exit(EXIT_CODES.CONFIG);
     }
 
-    console.info('[Loaded environment data]\n\n');
+    log.verbose('[Loaded environment data]');
+    log.verbose('Authenticating with ' + AUTH_MODE_LABELS[mode] + '\n\n');
   } else {
-    console.log(
-      'Required environment variables not set in .env file: CLOUDFLARE_API_KEY & CLOUDFLARE_USER_EMAIL'
+    log.error(
+      'Required environment variables not set: CLOUDFLARE_API_TOKEN, CLOUDFLARE_API_KEY & CLOUDFLARE_USER_EMAIL or CLOUDFLARE_USER_SERVICE_KEY'
     );
-    process.exit(1);
+    process.exit(EXIT_CODES.CONFIG);
   }
 }
@@ -0,0 +1,64 @@
+const fs = require('fs');
+const { version } = require('../package.json');
+
+// Variables a header template can use, as {name}
+const HEADER_VARIABLES = [
+  'zone',
+  'zone_id',
+  'account',
+  'account_id',
+  'exported_at',
+  'format',
+  'version',
+];
+
+function loadHeaderTemplate(file) {
+  let template;
+  try {
+    template = fs.readFileSync(file, 'utf8');
+  } catch (error) {
+    throw new Error('Reading header template: ' + error.message);
+  }
+  const unknown = (template.match(/\{[^{}\s]+\}/g) || [])
+    .map((variable) => variable.slice(1, -1))
+    .filter((name) => !HEADER_VARIABLES.includes(name));
+  if (unknown.length) {
+    throw new Error(
+      file +
+        ': unknown variable {' +
+        unknown[0] +
+        '}, expected one of ' +
+        HEADER_VARIABLES.join(', ')
+    );
+  }
+  return template.replace(/\r?\n$/, '');
+}
+
+// The template with its variables filled in, each line commented out with
+// the format's comment marker
+function renderHeader(template, values, comment) {
+  const filled = Object.assign({ version: version }, values);
+  return (
+    template
+      .replace(/\{([^{}\s]+)\}/g, (match, name) =>
+        filled[name] === undefined || filled[name] === null
+          ? ''
+          : String(filled[name])
+      )
+      .split(/\r?\n/)
+      .map((line) => (line ? comment + ' ' + line : comment))
+      .join('\n') + '\n'
+  );
+}
+
+// Timestamps in one fixed format, to the second in UTC
+function headerTimestamp(date) {
+  return date.toISOString().replace(/\.\d+Z$/, 'Z');
+}
+
+module.exports = {
+  HEADER_VARIABLES,
+  loadHeaderTemplate,
+  renderHeader,
+  headerTimestamp,
+};
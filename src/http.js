@@ -0,0 +1,120 @@
+const fs = require('fs');
+const https = require('https');
+const tls = require('tls');
+const { proxyFor, openTunnel } = require('./proxy');
+
+// Seconds before a whole request is abandoned, unless --timeout says otherwise
+const DEFAULT_TIMEOUT = 60;
+
+let settings = {};
+
+const PROXY_PROTOCOLS = ['http:', 'https:', 'socks5:', 'socks5h:'];
+
+function configureHttp(options) {
+  if (options.proxy) {
+    let url;
+    try {
+      url = new URL(options.proxy);
+    } catch (error) {
+      throw new Error('Invalid proxy URL ' + options.proxy);
+    }
+    if (!PROXY_PROTOCOLS.includes(url.protocol)) {
+      throw new Error(
+        'Unsupported proxy ' + url.protocol + ' expected http, https or socks5'
+      );
+    }
+  }
+  settings = Object.assign({}, options);
+  if (options.cacert) {
+    try {
+      // Trusted in addition to the bundled roots, not instead of them
+      const roots = tls.rootCertificates ? tls.rootCertificates : [];
+      settings.ca = roots.concat(fs.readFileSync(options.cacert, 'utf8'));
+    } catch (error) {
+      throw new Error('Unable to read CA bundle ' + options.cacert);
+    }
+  }
+}
+
+// Certificate checks for Cloudflare and for HTTPS proxies
+function tlsOptions() {
+  const options = {};
+  if (settings.ca) options.ca = settings.ca;
+  if (settings.insecure) options.rejectUnauthorized = false;
+  return options;
+}
+
+// Bounds the time to connect and the time waiting on a silent connection,
+// which the request timeout alone can't tell apart
+function applyTimeouts(socket, host) {
+  if (settings.connectTimeout) {
+    const timer = setTimeout(() => {
+      socket.destroy(
+        new Error(
+          'Connecting to ' +
+            host +
+            ' timed out after ' +
+            settings.connectTimeout +
+            's'
+        )
+      );
+    }, settings.connectTimeout * 1000);
+    socket.once('secureConnect', () => clearTimeout(timer));
+    socket.once('close', () => clearTimeout(timer));
+  }
+  if (settings.readTimeout) {
+    socket.setTimeout(settings.readTimeout * 1000, () => {
+      socket.destroy(
+        new Error(
+          'No data from ' + host + ' for ' + settings.readTimeout + 's'
+        )
+      );
+    });
+  }
+  return socket;
+}
+
+class CloudflareAgent extends https.Agent {
+  createConnection(options, callback) {
+    const proxy = proxyFor(options.host, settings.proxy);
+    if (!proxy) {
+      return applyTimeouts(super.createConnection(options), options.host);
+    }
+
+    // Tunnel through the proxy, then run TLS to Cloudflare inside the tunnel
+    const timeout = settings.connectTimeout ? settings.connectTimeout * 1000 : 0;
+    openTunnel(
+      proxy,
+      options.host,
+      Number(options.port),
+      timeout,
+      tlsOptions()
+    ).then(
+      (tunnel) => {
+        const socket = tls.connect(
+          Object.assign({}, options, {
+            socket: tunnel,
+            servername: options.servername
+              ? options.servername
+              : options.host,
+          })
+        );
+        callback(null, applyTimeouts(socket, options.host));
+      },
+      (error) => callback(error)
+    );
+  }
+}
+
+// Settings shared by every API client, Cloudflare's and the secret stores'
+function httpConfig() {
+  const timeout = settings.timeout ? settings.timeout : DEFAULT_TIMEOUT;
+  return {
+    timeout: timeout * 1000,
+    httpsAgent: new CloudflareAgent(tlsOptions()),
+    // Proxies are handled by the agent, axios would send HTTPS requests in the clear
+    proxy: false,
+  };
+}
+
+module.exports = { configureHttp, httpConfig };
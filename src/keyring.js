@@ -0,0 +1,206 @@
+const { spawnSync } = require('child_process');
+
+// Credentials are stored under this service name, one entry per variable
+const SERVICE = 'export-cloudflare-dns';
+const CREDENTIAL_VARIABLES = [
+  'CLOUDFLARE_API_TOKEN',
+  'CLOUDFLARE_API_KEY',
+  'CLOUDFLARE_USER_EMAIL',
+  'CLOUDFLARE_USER_SERVICE_KEY',
+];
+
+// Windows Credential Manager has no CLI for reading secrets, and cmdkey takes
+// them on the command line, so go through CredRead and CredWrite
+const WINDOWS_CRED_API = `
+$sig = @'
+[DllImport("advapi32.dll", SetLastError=true, CharSet=CharSet.Unicode)]
+public static extern bool CredRead(string target, int type, int flags, out IntPtr cred);
+[DllImport("advapi32.dll", SetLastError=true, CharSet=CharSet.Unicode)]
+public static extern bool CredWrite(ref CREDENTIAL cred, int flags);
+[StructLayout(LayoutKind.Sequential, CharSet=CharSet.Unicode)]
+public struct CREDENTIAL { public int Flags; public int Type; public string TargetName;
+  public string Comment; public long LastWritten; public int CredentialBlobSize;
+  public IntPtr CredentialBlob; public int Persist; public int AttributeCount;
+  public IntPtr Attributes; public string TargetAlias; public string UserName; }
+'@
+$api = Add-Type -MemberDefinition $sig -Name CredApi -Namespace Cf -PassThru
+`;
+
+const WINDOWS_CRED_READ =
+  WINDOWS_CRED_API +
+  `
+$ptr = [IntPtr]::Zero
+if ($api::CredRead($env:CF_KEYRING_TARGET, 1, 0, [ref]$ptr)) {
+  $c = [Runtime.InteropServices.Marshal]::PtrToStructure($ptr, [type]'Cf.CredApi+CREDENTIAL')
+  [Runtime.InteropServices.Marshal]::PtrToStringUni($c.CredentialBlob, $c.CredentialBlobSize / 2)
+}
+`;
+
+// The secret is read from stdin, a generic credential persisted on the machine
+const WINDOWS_CRED_WRITE =
+  WINDOWS_CRED_API +
+  `
+$secret = [Console]::In.ReadToEnd()
+$c = New-Object Cf.CredApi+CREDENTIAL
+$c.Type = 1
+$c.Persist = 2
+$c.TargetName = $env:CF_KEYRING_TARGET
+$c.UserName = $env:CF_KEYRING_USER
+$c.CredentialBlobSize = $secret.Length * 2
+$c.CredentialBlob = [Runtime.InteropServices.Marshal]::StringToCoTaskMemUni($secret)
+$ok = $api::CredWrite([ref]$c, 0)
+$code = [Runtime.InteropServices.Marshal]::GetLastWin32Error()
+[Runtime.InteropServices.Marshal]::ZeroFreeCoTaskMemUnicode($c.CredentialBlob)
+if (-not $ok) { [Console]::Error.WriteLine('CredWrite failed with error ' + $code); exit 1 }
+`;
+
+// The command the platform's keyring is reached through
+function keyringTool() {
+  if (process.platform == 'darwin') return 'security';
+  if (process.platform == 'win32') return 'powershell';
+  return 'secret-tool';
+}
+
+function run(command, commandArgs, options) {
+  const result = spawnSync(
+    command,
+    commandArgs,
+    Object.assign({ encoding: 'utf8' }, options)
+  );
+  if (result.error) {
+    throw new Error(
+      'Unable to run ' + command + ' for keyring access: ' + result.error.message
+    );
+  }
+  return result;
+}
+
+// Secrets never go on the command line, where other users could see them in
+// the process list
+function setSecret(account, secret) {
+  let result;
+  if (process.platform == 'darwin') {
+    // With -w last and no value, security prompts for the secret twice
+    result = run(
+      'security',
+      ['add-generic-password', '-U', '-s', SERVICE, '-a', account, '-w'],
+      { input: secret + '\n' + secret + '\n' }
+    );
+  } else if (process.platform == 'win32') {
+    result = run(
+      'powershell',
+      ['-NoProfile', '-NonInteractive', '-Command', WINDOWS_CRED_WRITE],
+      {
+        input: secret,
+        env: Object.assign({}, process.env, {
+          CF_KEYRING_TARGET: SERVICE + ':' + account,
+          CF_KEYRING_USER: account,
+        }),
+      }
+    );
+  } else {
+    result = run(
+      'secret-tool',
+      [
+        'store',
+        '--label=' + SERVICE + ' ' + account,
+        'service',
+        SERVICE,
+        'account',
+        account,
+      ],
+      { input: secret }
+    );
+  }
+  if (result.status !== 0) {
+    throw new Error(
+      'Keyring refused to store ' + account + ': ' + result.stderr.trim()
+    );
+  }
+}
+
+function getSecret(account) {
+  let result;
+  if (process.platform == 'darwin') {
+    result = run('security', [
+      'find-generic-password',
+      '-s',
+      SERVICE,
+      '-a',
+      account,
+      '-w',
+    ]);
+  } else if (process.platform == 'win32') {
+    result = run(
+      'powershell',
+      ['-NoProfile', '-NonInteractive', '-Command', WINDOWS_CRED_READ],
+      {
+        env: Object.assign({}, process.env, {
+          CF_KEYRING_TARGET: SERVICE + ':' + account,
+        }),
+      }
+    );
+  } else {
+    result = run('secret-tool', [
+      'lookup',
+      'service',
+      SERVICE,
+      'account',
+      account,
+    ]);
+  }
+  const secret = result.status === 0 ? result.stdout.replace(/\r?\n$/, '') : '';
+  return secret ? secret : null;
+}
+
+function deleteSecret(account) {
+  if (process.platform == 'darwin') {
+    run('security', ['delete-generic-password', '-s', SERVICE, '-a', account]);
+  } else if (process.platform == 'win32') {
+    run('cmdkey', ['/delete:' + SERVICE + ':' + account]);
+  } else {
+    run('secret-tool', ['clear', 'service', SERVICE, 'account', account]);
+  }
+}
+
+// Save every credential variable currently set, returning the names stored.
+// Entries for the others are removed, so an old token cannot take precedence
+// over a newly stored global key.
+function storeCredentials(env) {
+  const stored = [];
+  CREDENTIAL_VARIABLES.forEach((name) => {
+    if (env[name] && env[name] != 'NULL') {
+      setSecret(name, env[name]);
+      stored.push(name);
+    } else {
+      deleteSecret(name);
+    }
+  });
+  return stored;
+}
+
+// Copy keyring credentials into env without overriding values already set
+function loadCredentials(env) {
+  const loaded = [];
+  CREDENTIAL_VARIABLES.forEach((name) => {
+    if (env[name]) return;
+    const secret = getSecret(name);
+    if (secret) {
+      env[name] = secret;
+      loaded.push(name);
+    }
+  });
+  return loaded;
+}
+
+function clearCredentials() {
+  CREDENTIAL_VARIABLES.forEach((name) => deleteSecret(name));
+}
+
+module.exports = {
+  CREDENTIAL_VARIABLES,
+  keyringTool,
+  storeCredentials,
+  loadCredentials,
+  clearCredentials,
+};
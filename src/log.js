@@ -0,0 +1,79 @@
+// Output levels: -q shows only errors, the default a summary, -v per-zone
+// progress and -vv every API request
+const QUIET = 0;
+const NORMAL = 1;
+const VERBOSE = 2;
+const DEBUG = 3;
+
+const COLORS = { red: 31, green: 32, yellow: 33, cyan: 36 };
+
+let level = NORMAL;
+let output = process.stdout;
+let color = true;
+let progress = null;
+
+function setLevel(quiet, verbose) {
+  level = quiet ? QUIET : NORMAL + (verbose ? verbose : 0);
+}
+
+// Send progress to stderr when stdout carries the command's data
+function useStderr() {
+  output = process.stderr;
+}
+
+// Colors are only used on terminals, and never when turned off
+function setColor(enabled) {
+  color = enabled;
+}
+
+// Messages are printed above a progress bar, which is redrawn after them
+function setProgress(bar) {
+  progress = bar;
+}
+
+function print(stream, message, name) {
+  if (progress) progress.clear();
+  const colored =
+    name && color && stream.isTTY
+      ? '\x1b[' + COLORS[name] + 'm' + message + '\x1b[0m'
+      : message;
+  stream.write(colored + '\n');
+  if (progress) progress.render();
+}
+
+function error(message) {
+  print(process.stderr, 'Error:' + message, 'red');
+}
+
+function warn(message) {
+  if (level >= NORMAL) print(process.stderr, 'Warning:' + message, 'yellow');
+}
+
+function success(message) {
+  if (level >= NORMAL) print(output, message, 'green');
+}
+
+function info(message, name) {
+  if (level >= NORMAL) print(output, message, name);
+}
+
+function verbose(message) {
+  if (level >= VERBOSE) print(output, message);
+}
+
+function debug(message) {
+  if (level >= DEBUG) print(output, message);
+}
+
+module.exports = {
+  setLevel,
+  useStderr,
+  setColor,
+  setProgress,
+  error,
+  warn,
+  success,
+  info,
+  verbose,
+  debug,
+};
@@ -0,0 +1,64 @@
+const {
+  PROGRAM,
+  COMMANDS,
+  OPTIONS,
+  EXIT_CODE_DESCRIPTIONS,
+} = require('./cli');
+const { version, description } = require('../package.json');
+
+// Escape text for roff, where backslashes, dashes and leading dots are special
+function roff(text) {
+  return text
+    .replace(/\\/g, '\\e')
+    .replace(/-/g, '\\-')
+    .replace(/^([.'])/, '\\&$1');
+}
+
+function optionEntry(name) {
+  const option = OPTIONS[name];
+  const flag =
+    (option.short ? '\\fB\\-' + option.short + '\\fR, ' : '') +
+    '\\fB\\-\\-' +
+    roff(name) +
+    '\\fR' +
+    (option.value ? ' \\fI' + roff(option.value) + '\\fR' : '');
+  const choices = option.choices ? ' (' + option.choices.join('|') + ')' : '';
+  const commands = option.commands
+    ? ' Only for ' + option.commands.join(', ') + '.'
+    : '';
+  return ['.TP', flag, roff(option.description + choices + '.' + commands)];
+}
+
+// Render a man page from the command and option definitions
+function manPage() {
+  const lines = [
+    '.TH ' + roff(PROGRAM.toUpperCase()) + ' 1 "" "' + version + '"',
+    '.SH NAME',
+    roff(PROGRAM + ' - ' + description),
+    '.SH SYNOPSIS',
+    '\\fB' + roff(PROGRAM) + '\\fR [\\fIcommand\\fR] [\\fIoptions\\fR]',
+    '.SH DESCRIPTION',
+    roff(
+      'Exports the DNS records of every zone on a Cloudflare account, one file per zone.'
+    ),
+    '.SH COMMANDS',
+  ];
+  Object.keys(COMMANDS).forEach((name) => {
+    lines.push('.TP', '\\fB' + roff(COMMANDS[name].usage) + '\\fR');
+    lines.push(roff(COMMANDS[name].description));
+  });
+
+  lines.push('.SH OPTIONS');
+  Object.keys(OPTIONS)
+    .filter((name) => !OPTIONS[name].hidden)
+    .forEach((name) => lines.push(...optionEntry(name)));
+
+  lines.push('.SH EXIT STATUS');
+  EXIT_CODE_DESCRIPTIONS.forEach((entry) => {
+    lines.push('.TP', '\\fB' + entry[0] + '\\fR', roff(entry[1]));
+  });
+
+  return lines.join('\n') + '\n';
+}
+
+module.exports = { manPage };
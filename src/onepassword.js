@@ -0,0 +1,26 @@
+const { spawnSync } = require('child_process');
+
+// Credential values of the form op://vault/item/field are resolved by the 1Password CLI
+function isOnePasswordReference(value) {
+  return Boolean(value && value.startsWith('op://'));
+}
+
+function readOnePasswordSecret(reference) {
+  const result = spawnSync('op', ['read', '--no-newline', reference], {
+    encoding: 'utf8',
+    stdio: ['inherit', 'pipe', 'pipe'],
+  });
+  if (result.error) {
+    throw new Error(
+      'Unable to run the 1Password CLI (op): ' + result.error.message
+    );
+  }
+  if (result.status !== 0) {
+    throw new Error(
+      'op read ' + reference + ' failed: ' + result.stderr.trim()
+    );
+  }
+  return result.stdout;
+}
+
+module.exports = { isOnePasswordReference, readOnePasswordSecret };
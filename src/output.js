@@ -0,0 +1,111 @@
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+const { ARCHIVES } = require('./archive');
+const { CODECS } = require('./compress');
+
+// Writers take each exported file by its path relative to the output
+// directory and return where it ended up. finish() runs once all zones are
+// written and returns the path of anything written at the end. Writers can
+// be layered, each passing what it writes on to the next.
+
+// Write to a temporary name first, so a partial file never has the real name
+function writeAtomically(file, contents) {
+  const partial = file + '.partial';
+  fs.writeFileSync(partial, contents);
+  fs.renameSync(partial, file);
+}
+
+function directoryWriter(dir) {
+  return {
+    write(name, contents) {
+      const file = path.join(dir, name);
+      if (!fs.existsSync(path.dirname(file))) {
+        fs.mkdirSync(path.dirname(file), { recursive: true });
+      }
+      writeAtomically(file, contents);
+      return file;
+    },
+    finish() {
+      return null;
+    },
+  };
+}
+
+function stdoutWriter() {
+  return {
+    write(name, contents) {
+      process.stdout.write(contents);
+      return '-';
+    },
+    finish() {
+      return null;
+    },
+  };
+}
+
+// Compresses each file on its way to the next writer, adding the extension
+function compressingWriter(next, codec, level) {
+  const compressor = CODECS[codec];
+  const compressionLevel = level ? level : compressor.levels.default;
+  return {
+    write(name, contents) {
+      return next.write(
+        name + '.' + compressor.extension,
+        compressor.compress(Buffer.from(contents), compressionLevel)
+      );
+    },
+    finish(details) {
+      return next.finish(details);
+    },
+  };
+}
+
+function archiveName(type, date) {
+  const timestamp = date.toISOString().replace(/[-:]|\.\d+/g, '');
+  return 'cloudflare-dns-' + timestamp + '.' + ARCHIVES[type].extension;
+}
+
+// Collects the files and passes them on as a single archive, with a
+// manifest.json listing each file and its checksum
+function archiveWriter(type, next) {
+  const started = new Date();
+  const entries = [];
+  return {
+    write(name, contents) {
+      entries.push({
+        name: name,
+        contents: Buffer.from(contents),
+        mtime: new Date(),
+      });
+      return name;
+    },
+    finish(details) {
+      const files = entries.map((entry) => ({
+        name: entry.name,
+        bytes: entry.contents.length,
+        sha256: crypto
+          .createHash('sha256')
+          .update(entry.contents)
+          .digest('hex'),
+      }));
+      const manifest = Object.assign({}, details, { files: files });
+      const archive = ARCHIVES[type].build(
+        entries.concat({
+          name: 'manifest.json',
+          contents: Buffer.from(JSON.stringify(manifest, null, 2) + '\n'),
+          mtime: new Date(),
+        })
+      );
+      return next.write(archiveName(type, started), archive);
+    },
+  };
+}
+
+module.exports = {
+  directoryWriter,
+  stdoutWriter,
+  compressingWriter,
+  archiveWriter,
+  archiveName,
+};
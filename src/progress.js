@@ -0,0 +1,53 @@
+const BAR_WIDTH = 30;
+
+function formatDuration(seconds) {
+  seconds = Math.round(seconds);
+  return seconds >= 60
+    ? Math.floor(seconds / 60) + 'm' + (seconds % 60) + 's'
+    : seconds + 's';
+}
+
+// A single line progress bar showing zones done out of total, the zone most
+// recently started and an estimate of the time left
+function createProgress(total, stream) {
+  const started = Date.now();
+  let done = 0;
+  let current = '';
+
+  const bar = {
+    start(name) {
+      current = name;
+      bar.render();
+    },
+    tick() {
+      done++;
+      bar.render();
+    },
+    render() {
+      const filled = Math.round((done / total) * BAR_WIDTH);
+      const elapsed = (Date.now() - started) / 1000;
+      const eta = done
+        ? ' ETA ' + formatDuration((elapsed / done) * (total - done))
+        : '';
+      const line =
+        '[' +
+        '#'.repeat(filled) +
+        '-'.repeat(BAR_WIDTH - filled) +
+        '] ' +
+        done +
+        '/' +
+        total +
+        eta +
+        ' ' +
+        current;
+      const columns = stream.columns ? stream.columns - 1 : line.length;
+      stream.write('\r\x1b[K' + line.slice(0, columns));
+    },
+    clear() {
+      stream.write('\r\x1b[K');
+    },
+  };
+  return bar;
+}
+
+module.exports = { createProgress };
@@ -0,0 +1,34 @@
+const readline = require('readline');
+const { Writable } = require('stream');
+
+function ask(question, hidden) {
+  // Route readline's echo through a stream we can mute while typing
+  const output = new Writable({
+    write(chunk, encoding, callback) {
+      if (!output.muted) process.stdout.write(chunk, encoding);
+      callback();
+    },
+  });
+  output.muted = false;
+
+  const rl = readline.createInterface({
+    input: process.stdin,
+    output: output,
+    terminal: true,
+  });
+
+  return new Promise((resolve) => {
+    rl.question(question, (answer) => {
+      rl.close();
+      if (hidden) process.stdout.write('\n');
+      resolve(answer.trim());
+    });
+    output.muted = Boolean(hidden);
+  });
+}
+
+function askHidden(question) {
+  return ask(question, true);
+}
+
+module.exports = { ask, askHidden };
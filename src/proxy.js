@@ -0,0 +1,177 @@
+const net = require('net');
+const tls = require('tls');
+
+// The proxy to use for a host: --proxy, then the usual environment variables,
+// skipping hosts listed in NO_PROXY
+function proxyFor(host, explicit) {
+  const env = process.env;
+  const proxy = explicit
+    ? explicit
+    : env.HTTPS_PROXY || env.https_proxy || env.ALL_PROXY || env.all_proxy;
+  if (!proxy) return null;
+
+  const noProxy = (env.NO_PROXY || env.no_proxy || '')
+    .split(',')
+    .map((entry) => entry.trim().replace(/^\*?\./, ''))
+    .filter((entry) => entry);
+  const excluded = noProxy.some(
+    (entry) => entry == '*' || host == entry || host.endsWith('.' + entry)
+  );
+  return excluded && !explicit ? null : new URL(proxy);
+}
+
+// Buffers socket data so handshake replies can be read in pieces
+function reader(socket) {
+  let buffered = Buffer.alloc(0);
+  let pending = null;
+
+  const check = () => {
+    if (!pending) return;
+    let length = pending.length;
+    if (pending.until) {
+      // Wait for more data until the marker has arrived
+      const at = buffered.indexOf(pending.until);
+      if (at === -1) return;
+      length = at + pending.until.length;
+    }
+    if (buffered.length >= length) {
+      const resolve = pending.resolve;
+      pending = null;
+      const chunk = buffered.slice(0, length);
+      buffered = buffered.slice(length);
+      resolve(chunk);
+    }
+  };
+  const onData = (data) => {
+    buffered = Buffer.concat([buffered, data]);
+    check();
+  };
+  const onError = (error) => {
+    if (pending) pending.reject(error);
+  };
+  const onClose = () => onError(new Error('Proxy closed the connection'));
+  socket.on('data', onData);
+  socket.on('error', onError);
+  socket.on('close', onClose);
+
+  const wait = (request) =>
+    new Promise((resolve, reject) => {
+      pending = Object.assign({ resolve, reject }, request);
+      check();
+    });
+  return {
+    read: (length) => wait({ length: length }),
+    readUntil: (marker) => wait({ until: Buffer.from(marker) }),
+    done: () => {
+      socket.removeListener('data', onData);
+      socket.removeListener('error', onError);
+      socket.removeListener('close', onClose);
+    },
+  };
+}
+
+async function httpTunnel(socket, input, proxy, host, port) {
+  const target = host + ':' + port;
+  const headers = ['CONNECT ' + target + ' HTTP/1.1', 'Host: ' + target];
+  if (proxy.username) {
+    const credentials = Buffer.from(
+      decodeURIComponent(proxy.username) +
+        ':' +
+        decodeURIComponent(proxy.password)
+    );
+    headers.push('Proxy-Authorization: Basic ' + credentials.toString('base64'));
+  }
+  socket.write(headers.join('\r\n') + '\r\n\r\n');
+
+  const response = (await input.readUntil('\r\n\r\n')).toString();
+  const status = response.split('\r\n')[0];
+  if (!/^HTTP\/1\.[01] 2\d\d/.test(status)) {
+    throw new Error('Proxy refused the connection: ' + status);
+  }
+}
+
+async function socksTunnel(socket, input, proxy, host, port) {
+  const username = decodeURIComponent(proxy.username);
+  const password = decodeURIComponent(proxy.password);
+
+  // Offer no authentication, plus username/password when the URL has them
+  socket.write(Buffer.from(username ? [5, 2, 0, 2] : [5, 1, 0]));
+  const method = (await input.read(2))[1];
+  if (method == 2) {
+    socket.write(
+      Buffer.concat([
+        Buffer.from([1, Buffer.byteLength(username)]),
+        Buffer.from(username),
+        Buffer.from([Buffer.byteLength(password)]),
+        Buffer.from(password),
+      ])
+    );
+    if ((await input.read(2))[1] != 0) {
+      throw new Error('SOCKS proxy rejected the username or password');
+    }
+  } else if (method != 0) {
+    throw new Error(
+      'SOCKS proxy requires an unsupported authentication method'
+    );
+  }
+
+  // Connect by host name so the proxy resolves it
+  socket.write(
+    Buffer.concat([
+      Buffer.from([5, 1, 0, 3, Buffer.byteLength(host)]),
+      Buffer.from(host),
+      Buffer.from([port >> 8, port & 0xff]),
+    ])
+  );
+  const reply = await input.read(5);
+  if (reply[1] != 0) {
+    throw new Error('SOCKS proxy could not connect, reply code ' + reply[1]);
+  }
+  // The rest of the bound address, whose length depends on its type
+  const addressLength = reply[3] == 1 ? 4 : reply[3] == 4 ? 16 : reply[4] + 1;
+  await input.read(addressLength - 1 + 2);
+}
+
+// Open a connection to host:port through the proxy, ready for TLS
+async function openTunnel(proxy, host, port, timeout, tlsOptions) {
+  const socks = /^socks5h?:$/.test(proxy.protocol);
+  const secure = proxy.protocol == 'https:';
+  const proxyPort = proxy.port
+    ? Number(proxy.port)
+    : socks
+    ? 1080
+    : secure
+    ? 443
+    : 80;
+  const address = { host: proxy.hostname, port: proxyPort };
+  const socket = secure
+    ? tls.connect(
+        Object.assign({ servername: proxy.hostname }, tlsOptions, address)
+      )
+    : net.connect(address);
+
+  if (timeout) {
+    socket.setTimeout(timeout, () => {
+      socket.destroy(
+        new Error('Connecting through proxy ' + proxy.host + ' timed out')
+      );
+    });
+  }
+  const input = reader(socket);
+  try {
+    if (socks) {
+      await socksTunnel(socket, input, proxy, host, port);
+    } else {
+      await httpTunnel(socket, input, proxy, host, port);
+    }
+  } catch (error) {
+    socket.destroy();
+    throw error;
+  } finally {
+    input.done();
+  }
+  socket.setTimeout(0);
+  return socket;
+}
+
+module.exports = { proxyFor, openTunnel };
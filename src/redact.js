@@ -0,0 +1,70 @@
+// Variables whose values must never be printed
+const SECRET_VARIABLES = [
+  'CLOUDFLARE_API_TOKEN',
+  'CLOUDFLARE_API_KEY',
+  'CF_API_TOKEN',
+  'CF_API_KEY',
+  'CLOUDFLARE_USER_SERVICE_KEY',
+  'CF_API_USER_SERVICE_KEY',
+  'VAULT_TOKEN',
+  'AWS_SECRET_ACCESS_KEY',
+  'AWS_SESSION_TOKEN',
+];
+const REDACTED = '[REDACTED]';
+
+const secrets = new Set();
+
+function registerSecret(value) {
+  if (value) secrets.add(String(value));
+}
+
+function currentSecrets() {
+  const values = new Set(secrets);
+  SECRET_VARIABLES.forEach((name) => {
+    if (process.env[name]) values.add(process.env[name]);
+  });
+
+  // Skip placeholders and values too short to be real secrets
+  return Array.from(values).filter(
+    (value) => value != 'NULL' && value.length >= 6
+  );
+}
+
+function redact(text) {
+  let result = String(text);
+  currentSecrets().forEach((secret) => {
+    result = result.split(secret).join(REDACTED);
+  });
+  return result;
+}
+
+function wrapStream(stream) {
+  const write = stream.write.bind(stream);
+  // Only text is redacted. Buffers are binary output such as archives or
+  // compressed files, which rewriting would corrupt.
+  stream.write = function (chunk, encoding, callback) {
+    if (typeof chunk == 'string') {
+      return write(redact(chunk), encoding, callback);
+    }
+    return write(chunk, encoding, callback);
+  };
+}
+
+// Route every console write and crash report through redact()
+function installRedaction() {
+  wrapStream(process.stdout);
+  wrapStream(process.stderr);
+
+  const crash = (error) => {
+    // Output piped into something like head that has already exited
+    if (error && error.code == 'EPIPE') process.exit(0);
+    process.stderr.write(
+      (error && error.stack ? error.stack : String(error)) + '\n'
+    );
+    process.exit(1);
+  };
+  process.on('uncaughtException', crash);
+  process.on('unhandledRejection', crash);
+}
+
+module.exports = { registerSecret, redact, wrapStream, installRedaction };
@@ -0,0 +1,641 @@
+// Recreating the records of an exported zone through the API
+const crypto = require('crypto');
+const path = require('path');
+const { parseAnnotatedZone, unquote } = require('./zonefile');
+const { fromYaml } = require('./formats/yaml');
+
+// Cloudflare's automatic TTL, for files without one
+const AUTO_TTL = 1;
+
+// Types Cloudflare can proxy
+const PROXIABLE_TYPES = ['A', 'AAAA', 'CNAME'];
+
+// The fields of a record's data with quotes removed, at least count of them
+function dataFields(record, count) {
+  const fields = (record.data.match(/"(?:[^"\\]|\\.)*"|\S+/g) || []).map(
+    unquote
+  );
+  if (fields.length < count) {
+    throw new Error('Invalid ' + record.type + ' record ' + record.name);
+  }
+  return fields;
+}
+
+function numberField(record, field) {
+  const value = Number(field);
+  if (field === undefined || isNaN(value)) {
+    throw new Error('Invalid ' + record.type + ' record ' + record.name);
+  }
+  return value;
+}
+
+// Latitude, longitude and altitude, with the optional parts of RFC 1876
+const LOC_PATTERN = new RegExp(
+  '^(\\d+)(?:\\s+(\\d+)(?:\\s+([\\d.]+))?)?\\s+([NS])' +
+    '\\s+(\\d+)(?:\\s+(\\d+)(?:\\s+([\\d.]+))?)?\\s+([EW])' +
+    '\\s+(-?[\\d.]+)m?(?:\\s+([\\d.]+)m?(?:\\s+([\\d.]+)m?' +
+    '(?:\\s+([\\d.]+)m?)?)?)?$',
+  'i'
+);
+
+// Types the API takes as structured data rather than content. Hex and
+// base64 fields may be split over several words in a zone file.
+const DATA_TYPES = {
+  SRV: (record) => {
+    const parts = record.content.split(' ');
+    return {
+      priority: record.priority,
+      weight: Number(parts[0]),
+      port: Number(parts[1]),
+      target: parts[2],
+    };
+  },
+  CAA: (record) => {
+    const parts = /^(\d+)\s+(\S+)\s+(.*)$/.exec(record.data);
+    if (!parts) throw new Error('Invalid CAA record ' + record.name);
+    return {
+      flags: Number(parts[1]),
+      tag: parts[2],
+      value: unquote(parts[3]),
+    };
+  },
+  DS: (record) => {
+    const fields = dataFields(record, 4);
+    return {
+      key_tag: numberField(record, fields[0]),
+      algorithm: numberField(record, fields[1]),
+      digest_type: numberField(record, fields[2]),
+      digest: fields.slice(3).join(''),
+    };
+  },
+  TLSA: (record) => {
+    const fields = dataFields(record, 4);
+    return {
+      usage: numberField(record, fields[0]),
+      selector: numberField(record, fields[1]),
+      matching_type: numberField(record, fields[2]),
+      certificate: fields.slice(3).join(''),
+    };
+  },
+  SSHFP: (record) => {
+    const fields = dataFields(record, 3);
+    return {
+      algorithm: numberField(record, fields[0]),
+      type: numberField(record, fields[1]),
+      fingerprint: fields.slice(2).join(''),
+    };
+  },
+  CERT: (record) => {
+    const fields = dataFields(record, 4);
+    return {
+      type: numberField(record, fields[0]),
+      key_tag: numberField(record, fields[1]),
+      algorithm: numberField(record, fields[2]),
+      certificate: fields.slice(3).join(''),
+    };
+  },
+  NAPTR: (record) => {
+    const fields = dataFields(record, 6);
+    return {
+      order: numberField(record, fields[0]),
+      preference: numberField(record, fields[1]),
+      flags: fields[2],
+      service: fields[3],
+      regex: fields[4],
+      replacement: fields[5],
+    };
+  },
+  LOC: (record) => {
+    const parts = LOC_PATTERN.exec(record.data.trim());
+    if (!parts) throw new Error('Invalid LOC record ' + record.name);
+    const number = (part, fallback) =>
+      part === undefined ? fallback : Number(part);
+    return {
+      lat_degrees: Number(parts[1]),
+      lat_minutes: number(parts[2], 0),
+      lat_seconds: number(parts[3], 0),
+      lat_direction: parts[4].toUpperCase(),
+      long_degrees: Number(parts[5]),
+      long_minutes: number(parts[6], 0),
+      long_seconds: number(parts[7], 0),
+      long_direction: parts[8].toUpperCase(),
+      altitude: Number(parts[9]),
+      size: number(parts[10], 1),
+      precision_horz: number(parts[11], 10000),
+      precision_vert: number(parts[12], 10),
+    };
+  },
+  // The priority comes before these and is sent alongside the data
+  URI: (record) => {
+    const fields = dataFields(record, 3);
+    return {
+      weight: numberField(record, fields[1]),
+      target: fields[2],
+    };
+  },
+  HTTPS: serviceBinding,
+  SVCB: serviceBinding,
+};
+
+// The parameters are kept as written, quotes and all
+function serviceBinding(record) {
+  const parts = /^(\d+)\s+(\S+)\s*(.*)$/.exec(record.data);
+  if (!parts) {
+    throw new Error('Invalid ' + record.type + ' record ' + record.name);
+  }
+  return {
+    priority: Number(parts[1]),
+    target: parts[2],
+    value: parts[3],
+  };
+}
+
+// Types sent as data that also have a priority of their own
+const PRIORITY_DATA_TYPES = ['URI'];
+
+// The dns_records request body for a parsed record, with what its
+// annotation kept of the proxied status, comment and tags
+function recordPayload(record) {
+  const annotation = record.annotation ? record.annotation : {};
+  const payload = {
+    type: record.type,
+    name: record.name,
+    ttl: record.ttl !== null ? record.ttl : AUTO_TTL,
+  };
+  if (DATA_TYPES[record.type]) {
+    payload.data = DATA_TYPES[record.type](record);
+    if (PRIORITY_DATA_TYPES.includes(record.type)) {
+      payload.priority = record.priority;
+    }
+  } else {
+    payload.content = record.content;
+    if (record.priority !== undefined) payload.priority = record.priority;
+  }
+  if (annotation.proxied) payload.proxied = true;
+  if (annotation.comment) payload.comment = String(annotation.comment);
+  if (annotation.tags) payload.tags = String(annotation.tags).split(',');
+  return payload;
+}
+
+// A record as the API returned it, from a json or yaml export, keeps every
+// setting, so those are all restored
+function documentPayload(record) {
+  const payload = {
+    type: record.type,
+    name: record.name,
+    ttl: record.ttl ? record.ttl : AUTO_TTL,
+  };
+  const hasPriority = record.priority !== undefined && record.priority !== null;
+  if (record.data && typeof record.data == 'object') {
+    payload.data = record.data;
+    if (PRIORITY_DATA_TYPES.includes(record.type) && hasPriority) {
+      payload.priority = record.priority;
+    }
+  } else {
+    payload.content = record.content;
+    if (hasPriority) payload.priority = record.priority;
+  }
+  if (PROXIABLE_TYPES.includes(record.type)) {
+    payload.proxied = Boolean(record.proxied);
+  }
+  payload.comment = record.comment ? record.comment : '';
+  payload.tags = record.tags ? record.tags : [];
+  return payload;
+}
+
+function zoneFileRecords(text, zoneName) {
+  const parsed = parseAnnotatedZone(text, zoneName).records;
+  const payloads = parsed.map(recordPayload);
+  // Annotations only mark proxied records, so in an annotated file the
+  // others are known not to be
+  if (parsed.some((record) => record.annotation)) {
+    payloads
+      .filter((payload) => PROXIABLE_TYPES.includes(payload.type))
+      .forEach((payload) => (payload.proxied = Boolean(payload.proxied)));
+  }
+  return payloads;
+}
+
+// A json or yaml export has one zone, or a list of them with --single-file
+function documentRecords(document, zoneName) {
+  const documents = Array.isArray(document) ? document : [document];
+  const found = documents.find(
+    (zone) =>
+      zone &&
+      typeof zone.zone == 'string' &&
+      zone.zone.toLowerCase() == zoneName.toLowerCase()
+  );
+  if (!found || !Array.isArray(found.records)) {
+    throw new Error('No records of ' + zoneName + ' in the file');
+  }
+  return found.records.map(documentPayload);
+}
+
+const DOCUMENT_READERS = {
+  '.json': JSON.parse,
+  '.yaml': fromYaml,
+  '.yml': fromYaml,
+};
+
+function inZone(name, zoneName) {
+  const lower = name.toLowerCase();
+  return lower == zoneName || lower.endsWith('.' + zoneName);
+}
+
+// Cloudflare makes its own SOA and apex NS records, so those are left out
+function zonePayloads(payloads, zoneName) {
+  const zone = zoneName.toLowerCase();
+  const outside = payloads.filter((payload) => !inZone(payload.name, zone));
+  if (outside.length) {
+    throw new Error(
+      'Not in ' + zoneName + ': ' + outside[0].name + ' ' + outside[0].type
+    );
+  }
+  const apex = (payload) => payload.name.toLowerCase() == zone;
+  return payloads
+    .filter((payload) => payload.type != 'SOA')
+    .filter((payload) => payload.type != 'NS' || !apex(payload));
+}
+
+// The records a snapshot says the zone should have, as request bodies,
+// from a zone file or a json or yaml export
+function readSnapshot(text, file, zoneName) {
+  const reader = DOCUMENT_READERS[path.extname(file).toLowerCase()];
+  const payloads = reader
+    ? documentRecords(reader(text), zoneName)
+    : zoneFileRecords(text, zoneName);
+  return zonePayloads(payloads, zoneName);
+}
+
+// Another zone's API records as request bodies, to copy them
+function copyRecords(records, zoneName) {
+  return zonePayloads(records.map(documentPayload), zoneName);
+}
+
+// Rewrites such as example.com=staging.example.com as [from, to] pairs
+function parseRewrites(items) {
+  return items.map((item) => {
+    const parts = item.split('=');
+    if (parts.length != 2 || !parts[0] || !parts[1]) {
+      throw new Error('Expected a rewrite such as a.com=b.com, got ' + item);
+    }
+    return [parts[0].toLowerCase(), parts[1].toLowerCase()];
+  });
+}
+
+// Every whole domain name from, including the end of longer names as in
+// www.example.com, becomes to. Names that only start with it, as
+// example.com.au does, are left alone.
+function rewriteText(text, rewrites) {
+  return rewrites.reduce((result, rewrite) => {
+    const from = rewrite[0].replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+    const pattern = new RegExp(
+      '(^|[^a-z0-9-])' + from + '(?!\\.?[a-z0-9-])',
+      'gi'
+    );
+    return result.replace(pattern, (match, before) => before + rewrite[1]);
+  }, text);
+}
+
+// A copy of an API record with its name, content and data rewritten
+function rewriteRecord(record, rewrites) {
+  const copy = Object.assign({}, record, {
+    name: rewriteText(record.name, rewrites),
+  });
+  if (typeof record.content == 'string') {
+    copy.content = rewriteText(record.content, rewrites);
+  }
+  if (record.data && typeof record.data == 'object') {
+    copy.data = {};
+    Object.keys(record.data).forEach((key) => {
+      const value = record.data[key];
+      copy.data[key] =
+        typeof value == 'string' ? rewriteText(value, rewrites) : value;
+    });
+  }
+  return copy;
+}
+
+// Whether an API record has the payload's data, whatever its settings
+function sameData(payload, record) {
+  if (record.name.toLowerCase() != payload.name.toLowerCase()) return false;
+  if (record.type != payload.type) return false;
+  if (payload.data) {
+    const data = record.data ? record.data : {};
+    return (
+      (payload.priority === undefined || record.priority == payload.priority) &&
+      Object.keys(payload.data).every(
+        (key) => String(data[key]) == String(payload.data[key])
+      )
+    );
+  }
+  return (
+    record.content == payload.content &&
+    (payload.priority === undefined || record.priority == payload.priority)
+  );
+}
+
+// The existing API record the payload would duplicate, if any
+function existingRecord(payload, records) {
+  return records.find((record) => sameData(payload, record));
+}
+
+// Record settings besides the data, each compared only where the snapshot
+// has it, so a plain zone file leaves proxying as it is
+const SETTINGS = {
+  ttl: (value) => value,
+  proxied: (value) => Boolean(value),
+  comment: (value) => (value ? value : ''),
+  tags: (value) => (value ? value.slice().sort().join(',') : ''),
+};
+
+function changedSettings(payload, record) {
+  return Object.keys(SETTINGS).filter(
+    (key) =>
+      payload[key] !== undefined &&
+      SETTINGS[key](payload[key]) != SETTINGS[key](record[key])
+  );
+}
+
+// The data of a payload or API record on one line, e.g. 10 mx.example.com
+function dataText(record) {
+  const priority =
+    record.priority !== undefined && record.priority !== null
+      ? record.priority + ' '
+      : '';
+  if (record.data && typeof record.data == 'object') {
+    return (
+      (PRIORITY_DATA_TYPES.includes(record.type) ? priority : '') +
+      Object.keys(record.data)
+        .map((key) => record.data[key])
+        .join(' ')
+    );
+  }
+  return priority + record.content;
+}
+
+// How a payload or API record is shown in plans and errors, e.g.
+// MX example.com 10 mx.example.com
+function describeRecord(record) {
+  return record.type + ' ' + record.name + ' ' + dataText(record);
+}
+
+// How a planned change is shown, e.g.
+// ~ A www.example.com 192.0.2.1 -> 192.0.2.2 (ttl 1 -> 300)
+function describeChange(change) {
+  if (change.action == 'create') return '+ ' + describeRecord(change.payload);
+  if (change.action == 'delete') return '- ' + describeRecord(change.record);
+  let line = '~ ' + describeRecord(change.record);
+  if (!sameData(change.payload, change.record)) {
+    line += ' -> ' + dataText(change.payload);
+  }
+  const settings = changedSettings(change.payload, change.record).map(
+    (key) =>
+      key +
+      ' ' +
+      JSON.stringify(change.record[key]) +
+      ' -> ' +
+      JSON.stringify(change.payload[key])
+  );
+  return settings.length ? line + ' (' + settings.join(', ') + ')' : line;
+}
+
+// A record as one line of a plan diff, with the settings a zone file
+// leaves out at the end, e.g.
+// www.example.com.  auto  CNAME  example.com  proxied
+function diffLine(record) {
+  const fields = [
+    record.name + '.',
+    record.ttl == AUTO_TTL ? 'auto' : record.ttl,
+    record.type,
+    dataText(record),
+  ];
+  if (record.proxied) fields.push('proxied');
+  if (record.comment) fields.push(JSON.stringify(record.comment));
+  if (record.tags && record.tags.length) fields.push(record.tags.join(','));
+  return fields.join('\t');
+}
+
+// The changes as a unified diff of records, from the zone as it is to the
+// zone as it will be, with a hunk per name. Lines come as { sign, text }
+// with the sign '-', '+', '@' for hunk headers or '' for the file names.
+function planDiff(changes, from, to) {
+  const lines = [
+    { sign: '', text: '--- ' + from },
+    { sign: '', text: '+++ ' + to },
+  ];
+  const name = (change) =>
+    (change.record ? change.record : change.payload).name.toLowerCase();
+  // The index keeps changes to one name in plan order, however sort works
+  const key = (entry) => [nameOrder(name(entry.change)), entry.index];
+  const sorted = changes
+    .map((change, index) => ({ change: change, index: index }))
+    .sort((a, b) => {
+      const first = key(a);
+      const second = key(b);
+      if (first[0] != second[0]) return first[0] < second[0] ? -1 : 1;
+      return first[1] - second[1];
+    })
+    .map((entry) => entry.change);
+  let hunk = null;
+  sorted.forEach((change) => {
+    if (name(change) != hunk) {
+      hunk = name(change);
+      lines.push({ sign: '@', text: '@@ ' + hunk + ' @@' });
+    }
+    if (change.record) {
+      lines.push({ sign: '-', text: '-' + diffLine(change.record) });
+    }
+    if (change.payload) {
+      // Updates keep whatever the payload does not set
+      const after = Object.assign({}, change.record, change.payload);
+      lines.push({ sign: '+', text: '+' + diffLine(after) });
+    }
+  });
+  return lines;
+}
+
+// Names sort by their labels from the right, so each is next to its parent
+function nameOrder(name) {
+  return name.split('.').reverse().join('\u0000');
+}
+
+// Cloudflare manages the apex NS records itself
+function managedRecord(record, zoneName) {
+  return (
+    record.type == 'NS' && record.name.toLowerCase() == zoneName.toLowerCase()
+  );
+}
+
+// The changes that make the zone's API records match the snapshot's
+// payloads, as { action, payload, record } with the action create, update
+// or delete. Without prune nothing the zone has is replaced or deleted,
+// only added to. Deletes come first, so a name can change type without the
+// API refusing a CNAME next to other records.
+function planChanges(payloads, records, zoneName, prune) {
+  const unmatched = records.filter(
+    (record) => !managedRecord(record, zoneName)
+  );
+  const take = (record) => unmatched.splice(unmatched.indexOf(record), 1)[0];
+  const updates = [];
+  const creates = [];
+
+  // Records with the same data are kept, with any settings updated
+  const pending = payloads.filter((payload) => {
+    const record = existingRecord(payload, unmatched);
+    if (!record) return true;
+    take(record);
+    if (changedSettings(payload, record).length) {
+      updates.push({ action: 'update', payload: payload, record: record });
+    }
+    return false;
+  });
+  // Otherwise a record of the same name and type is changed in place
+  pending.forEach((payload) => {
+    const record = unmatched.find(
+      (found) =>
+        found.name.toLowerCase() == payload.name.toLowerCase() &&
+        found.type == payload.type
+    );
+    if (record && prune) {
+      take(record);
+      updates.push({ action: 'update', payload: payload, record: record });
+    } else {
+      creates.push({ action: 'create', payload: payload });
+    }
+  });
+  const deletes = prune
+    ? unmatched.map((record) => ({ action: 'delete', record: record }))
+    : [];
+  return deletes.concat(updates, creates);
+}
+
+async function apiRequest(request) {
+  const response = await request;
+  if (!response.data.success) {
+    throw new Error(
+      response.data.errors.map((error) => error.message).join(', ')
+    );
+  }
+  return response.data.result;
+}
+
+// Updates only send what the snapshot has, leaving other settings as they are
+function applyChange(client, domain, change) {
+  const records = 'zones/' + domain.id + '/dns_records';
+  if (change.action == 'create') {
+    return apiRequest(client.post(records, change.payload));
+  } else if (change.action == 'update') {
+    return apiRequest(
+      client.patch(records + '/' + change.record.id, change.payload)
+    );
+  }
+  return apiRequest(client.delete(records + '/' + change.record.id));
+}
+
+// Attempts at a change the API keeps rate limiting before giving up
+const MAX_ATTEMPTS = 5;
+
+// Seconds to hold off after a 429, as the API says or doubling each time
+function retryDelay(response, attempt) {
+  const header = response.headers ? Number(response.headers['retry-after']) : 0;
+  return header > 0 ? header : Math.min(60, Math.pow(2, attempt));
+}
+
+// Apply a change when the throttle allows, waiting out 429 responses. Every
+// write shares the throttle, so they all slow down together.
+async function throttledChange(client, domain, change, throttle, onRetry) {
+  for (let attempt = 1; ; attempt++) {
+    await throttle.wait();
+    try {
+      return await applyChange(client, domain, change);
+    } catch (error) {
+      const status = error.response ? error.response.status : null;
+      if (status != 429 || attempt == MAX_ATTEMPTS) throw error;
+      const seconds = retryDelay(error.response, attempt);
+      if (onRetry) onRetry(seconds);
+      throttle.pause(seconds * 1000);
+    }
+  }
+}
+
+// Add a zone to the account, returning it as the zones API does
+function createZone(client, name, accountId) {
+  return apiRequest(
+    client.post('zones', {
+      name: name,
+      account: { id: accountId },
+      type: 'full',
+    })
+  );
+}
+
+// Version of the plan file layout, raised when it changes
+const PLAN_VERSION = 1;
+
+// The record fields a plan depends on, in a fixed order
+function recordState(record) {
+  return [
+    record.id,
+    record.name,
+    record.type,
+    record.content,
+    record.data ? record.data : null,
+    record.priority !== undefined ? record.priority : null,
+    record.ttl,
+    Boolean(record.proxied),
+    record.comment ? record.comment : null,
+    record.tags ? record.tags.slice().sort() : [],
+  ];
+}
+
+// A hash of the zone's records, to tell whether they changed since a plan
+function recordsDigest(records) {
+  const states = records
+    .map((record) => JSON.stringify(recordState(record)))
+    .sort();
+  return crypto.createHash('sha256').update(states.join('\n')).digest('hex');
+}
+
+// The plan file of plan, applied as it is by apply
+function planDocument(domain, snapshot, changes, records) {
+  return {
+    version: PLAN_VERSION,
+    created_at: new Date().toISOString(),
+    zone: domain.name,
+    zone_id: domain.id,
+    snapshot: snapshot,
+    records_digest: recordsDigest(records),
+    changes: changes,
+  };
+}
+
+function readPlan(text) {
+  let plan;
+  try {
+    plan = JSON.parse(text);
+  } catch (error) {
+    throw new Error('Not a plan file, it is not JSON');
+  }
+  if (plan.version != PLAN_VERSION || !Array.isArray(plan.changes)) {
+    throw new Error('Not a plan file of this version');
+  }
+  return plan;
+}
+
+module.exports = {
+  readSnapshot,
+  copyRecords,
+  parseRewrites,
+  rewriteRecord,
+  createZone,
+  existingRecord,
+  planChanges,
+  describeRecord,
+  describeChange,
+  planDiff,
+  throttledChange,
+  recordsDigest,
+  planDocument,
+  readPlan,
+};
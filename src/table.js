@@ -0,0 +1,22 @@
+// Render rows as a plain text table with a header and aligned columns
+function renderTable(headers, rows) {
+  const cells = [headers].concat(rows).map((row) =>
+    row.map((cell) => (cell === null || cell === undefined ? '' : String(cell)))
+  );
+  const widths = headers.map((header, column) =>
+    Math.max(...cells.map((row) => row[column].length))
+  );
+  const line = (row) =>
+    row
+      .map((cell, column) => cell.padEnd(widths[column]))
+      .join('  ')
+      .trimEnd();
+
+  return (
+    [line(cells[0]), widths.map((width) => '-'.repeat(width)).join('  ')]
+      .concat(cells.slice(1).map(line))
+      .join('\n') + '\n'
+  );
+}
+
+module.exports = { renderTable };
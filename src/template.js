@@ -0,0 +1,180 @@
+const fs = require('fs');
+
+// A small Mustache-style template language for --format template:
+//   {{name}}                 a value, looked up through the enclosing sections
+//   {{zone.name}}            a dotted path, {{.}} is the current item
+//   {{#records}}..{{/records}} repeated for each item in a list, or shown once
+//                            for any other value that is set
+//   {{^records}}..{{/records}} shown when the value is unset or an empty list
+//   {{@index}} {{@first}} {{@last}} the position in the innermost list
+//   {{! comment }}
+// Section and comment tags alone on a line take the whole line with them.
+
+const TAG = /\{\{\s*([#^/!]?)\s*([^}]*?)\s*\}\}/g;
+
+function lineOf(text, index) {
+  return text.slice(0, index).split('\n').length;
+}
+
+// Whether the tag at [start, end) has only whitespace around it on its line
+function standalone(text, start, end) {
+  const lineStart = text.lastIndexOf('\n', start - 1) + 1;
+  let lineEnd = text.indexOf('\n', end);
+  if (lineEnd == -1) lineEnd = text.length;
+  const blank = (part) => /^[ \t\r]*$/.test(part);
+  const before = text.slice(lineStart, start);
+  if (!blank(before) || !blank(text.slice(end, lineEnd))) return null;
+  return { start: lineStart, end: Math.min(lineEnd + 1, text.length) };
+}
+
+function parseTemplate(text) {
+  const root = { children: [] };
+  const open = [root];
+  let position = 0;
+  let match;
+  TAG.lastIndex = 0;
+  while ((match = TAG.exec(text))) {
+    const kind = match[1];
+    const name = match[2];
+    const line = lineOf(text, match.index);
+    let start = match.index;
+    let end = TAG.lastIndex;
+    if (kind) {
+      const whole = standalone(text, start, end);
+      if (whole) {
+        start = whole.start;
+        end = whole.end;
+      }
+    }
+    const current = open[open.length - 1];
+    if (start > position) current.children.push(text.slice(position, start));
+    position = end;
+    TAG.lastIndex = Math.max(TAG.lastIndex, end);
+
+    if (!name && kind != '!') {
+      throw new Error('line ' + line + ': empty tag');
+    } else if (kind == '!') {
+      continue;
+    } else if (kind == '#' || kind == '^') {
+      const section = {
+        name: name,
+        inverted: kind == '^',
+        line: line,
+        children: [],
+      };
+      current.children.push(section);
+      open.push(section);
+    } else if (kind == '/') {
+      if (open.length == 1) {
+        throw new Error('line ' + line + ': {{/' + name + '}} was not opened');
+      } else if (current.name != name) {
+        throw new Error(
+          'line ' +
+            line +
+            ': {{/' +
+            name +
+            '}} closes {{#' +
+            current.name +
+            '}} from line ' +
+            current.line
+        );
+      }
+      open.pop();
+    } else {
+      current.children.push({ variable: name, line: line });
+    }
+  }
+  if (open.length > 1) {
+    const section = open[open.length - 1];
+    throw new Error(
+      'line ' + section.line + ': {{#' + section.name + '}} is never closed'
+    );
+  }
+  if (position < text.length) root.children.push(text.slice(position));
+  return root.children;
+}
+
+// Looks a name up through the context stack, innermost first
+function lookup(stack, name) {
+  if (name == '.') return stack[stack.length - 1].value;
+  if (name.startsWith('@')) {
+    for (let i = stack.length - 1; i >= 0; i--) {
+      if (stack[i].list) return stack[i].list[name.slice(1)];
+    }
+    return undefined;
+  }
+  const parts = name.split('.');
+  for (let i = stack.length - 1; i >= 0; i--) {
+    const value = stack[i].value;
+    if (value !== null && typeof value == 'object' && parts[0] in value) {
+      return parts
+        .slice(1)
+        .reduce(
+          (found, part) =>
+            found === null || found === undefined ? found : found[part],
+          value[parts[0]]
+        );
+    }
+  }
+  return undefined;
+}
+
+function text(value) {
+  if (value === null || value === undefined) return '';
+  if (Array.isArray(value)) return value.map(text).join(', ');
+  if (typeof value == 'object') return JSON.stringify(value);
+  return String(value);
+}
+
+function renderNodes(nodes, stack) {
+  return nodes
+    .map((node) => {
+      if (typeof node == 'string') return node;
+      if (node.variable) return text(lookup(stack, node.variable));
+      const value = lookup(stack, node.name);
+      const empty = Array.isArray(value) ? value.length == 0 : !value;
+      if (node.inverted) return empty ? renderNodes(node.children, stack) : '';
+      if (empty) return '';
+      if (!Array.isArray(value)) {
+        return renderNodes(node.children, stack.concat({ value: value }));
+      }
+      return value
+        .map((item, index) =>
+          renderNodes(
+            node.children,
+            stack.concat({
+              value: item,
+              list: {
+                index: index,
+                first: index == 0,
+                last: index == value.length - 1,
+              },
+            })
+          )
+        )
+        .join('');
+    })
+    .join('');
+}
+
+// Returns a function rendering the template for a view object
+function compileTemplate(source) {
+  const nodes = parseTemplate(source);
+  return (view) => renderNodes(nodes, [{ value: view }]);
+}
+
+function loadTemplate(file) {
+  let source;
+  try {
+    source = fs.readFileSync(file, 'utf8');
+  } catch (error) {
+    throw new Error('Reading template: ' + error.message);
+  }
+  try {
+    return compileTemplate(source);
+  } catch (error) {
+    throw new Error(file + ': ' + error.message);
+  }
+}
+
+module.exports = { compileTemplate, loadTemplate };
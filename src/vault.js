@@ -0,0 +1,89 @@
+const axios = require('axios');
+const { httpConfig } = require('./http');
+
+function vaultRequest(env, path) {
+  const headers = { 'X-Vault-Token': env.VAULT_TOKEN };
+  if (env.VAULT_NAMESPACE) {
+    headers['X-Vault-Namespace'] = env.VAULT_NAMESPACE;
+  }
+  return axios.get(
+    'v1/' + path,
+    Object.assign(
+      {
+        baseURL: env.VAULT_ADDR.replace(/\/*$/, '/'),
+        headers: headers,
+      },
+      // The same proxy, CA bundle and timeouts as the Cloudflare API
+      httpConfig()
+    )
+  );
+}
+
+// The mount of a secret path and its KV version, as Vault reports them. When
+// the token may not look the mount up, the first part of the path is taken
+// as the mount and version 1 is assumed.
+async function secretMount(env, path) {
+  try {
+    const response = await vaultRequest(env, 'sys/internal/ui/mounts/' + path);
+    const mount = response.data.data;
+    return {
+      path: mount.path,
+      version: mount.options && mount.options.version == '2' ? 2 : 1,
+      detected: true,
+    };
+  } catch (error) {
+    return { path: path.split('/')[0] + '/', version: 1, detected: false };
+  }
+}
+
+// Read the Cloudflare token from a HashiCorp Vault KV secret. The KV version
+// comes from CLOUDFLARE_VAULT_KV_VERSION, or else from the secret's mount.
+async function readVaultToken(env) {
+  if (!env.VAULT_ADDR || !env.VAULT_TOKEN) {
+    throw new Error('VAULT_ADDR and VAULT_TOKEN must be set to use Vault');
+  }
+
+  const field = env.CLOUDFLARE_VAULT_FIELD ? env.CLOUDFLARE_VAULT_FIELD : 'token';
+  const path = env.CLOUDFLARE_VAULT_PATH.replace(/^\/+/, '');
+  const mount = await secretMount(env, path);
+  const version = env.CLOUDFLARE_VAULT_KV_VERSION
+    ? Number(env.CLOUDFLARE_VAULT_KV_VERSION)
+    : mount.version;
+  // KV v2 reads secrets under data/ in the mount, which the path may include
+  let secretPath = path;
+  if (version == 2 && path.startsWith(mount.path)) {
+    const rest = path.slice(mount.path.length);
+    if (!rest.startsWith('data/')) secretPath = mount.path + 'data/' + rest;
+  }
+
+  let response;
+  try {
+    response = await vaultRequest(env, secretPath);
+  } catch (error) {
+    const status = error.response ? ' (HTTP ' + error.response.status + ')' : '';
+    throw new Error(
+      'Unable to read Vault secret ' + env.CLOUDFLARE_VAULT_PATH + status
+    );
+  }
+
+  // KV v2 nests the secret one level deeper than KV v1
+  const secret = version == 2 ? response.data.data.data : response.data.data;
+  if (!secret || !secret[field]) {
+    const hint =
+      version == 1 && !mount.detected
+        ? ', pass --vault-kv-version 2 if it is a KV version 2 secret'
+        : '';
+    throw new Error(
+      'Vault secret ' +
+        env.CLOUDFLARE_VAULT_PATH +
+        ' has no "' +
+        field +
+        '" field' +
+        hint
+    );
+  }
+
+  return secret[field];
+}
+
+module.exports = { readVaultToken };
@@ -0,0 +1,90 @@
+const fs = require('fs');
+const path = require('path');
+const zlib = require('zlib');
+const { spawnSync } = require('child_process');
+const { PROGRAM } = require('./cli');
+const { keyringTool } = require('./keyring');
+const { proxyFor } = require('./proxy');
+const { version } = require('../package.json');
+
+// Arguments that make each optional tool exit at once, when it is installed
+const TOOL_CHECKS = {
+  security: ['help'],
+  powershell: ['-NoProfile', '-NonInteractive', '-Command', 'exit'],
+  'secret-tool': ['--version'],
+  op: ['--version'],
+  zstd: ['--version'],
+};
+
+function git(root, gitArgs) {
+  const result = spawnSync('git', gitArgs, { cwd: root, encoding: 'utf8' });
+  return result.status === 0 ? result.stdout.trim() : null;
+}
+
+function packageRoot() {
+  return fs.realpathSync(path.join(__dirname, '..'));
+}
+
+// Installed under node_modules in another project's checkout, git would
+// report that project's commit instead, so the checkout must be our own
+function ownCheckout(root) {
+  const topLevel = git(root, ['rev-parse', '--show-toplevel']);
+  return Boolean(topLevel) && fs.realpathSync(path.resolve(topLevel)) == root;
+}
+
+// The commit and its date when running from a git checkout, or the date
+// build.json was stamped with when the package was packed
+function buildInfo() {
+  const root = packageRoot();
+  if (ownCheckout(root)) {
+    const commit = git(root, ['log', '-1', '--format=%h %cI']);
+    if (commit) {
+      const parts = commit.split(' ');
+      return { commit: parts[0], date: parts[1] };
+    }
+  }
+  try {
+    const stamp = JSON.parse(
+      fs.readFileSync(path.join(root, 'build.json'), 'utf8')
+    );
+    return { commit: null, date: stamp.built_at };
+  } catch (error) {
+    return { commit: null, date: null };
+  }
+}
+
+function installed(tool) {
+  const result = spawnSync(tool, TOOL_CHECKS[tool], { stdio: 'ignore' });
+  return !result.error;
+}
+
+// Optional integrations this machine has, each with what provides it
+function features(endpoint, explicitProxy) {
+  const found = [];
+  if (installed(keyringTool())) found.push('keyring:' + keyringTool());
+  if (installed('op')) found.push('1password:op');
+  if (zlib.zstdCompressSync) {
+    found.push('zstd:node');
+  } else if (installed('zstd')) {
+    found.push('zstd:command');
+  }
+  const proxy = proxyFor(new URL(endpoint).hostname, explicitProxy);
+  if (proxy) found.push('proxy:' + proxy.protocol.replace(/:$/, ''));
+  return found.length ? found.join(' ') : 'none';
+}
+
+// Details worth including in a bug report
+function versionText(endpoint, explicitProxy) {
+  const build = buildInfo();
+  return (
+    [
+      PROGRAM + ' ' + version + (build.commit ? ' (' + build.commit + ')' : ''),
+      'built ' + (build.date ? build.date : 'unknown'),
+      'node ' + process.version + ' ' + process.platform + ' ' + process.arch,
+      'api ' + endpoint,
+      'features ' + features(endpoint, explicitProxy),
+    ].join('\n') + '\n'
+  );
+}
+
+module.exports = { versionText };
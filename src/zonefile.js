@@ -0,0 +1,415 @@
+const CLASSES = ['IN', 'CH', 'HS', 'CS'];
+
+// Record types whose data ends in a hostname, which Cloudflare stores without the dot
+const HOSTNAME_TYPES = ['CNAME', 'NS', 'PTR', 'MX', 'SRV', 'DNAME'];
+
+// Split zone text into logical lines of tokens, joining ( ) continuations and
+// dropping comments. Quoted strings are kept with their quotes.
+function tokenize(text) {
+  const lines = [];
+  let tokens = [];
+  let depth = 0;
+  let startsIndented = false;
+  let startLine = 1;
+  let token = '';
+  let quoted = false;
+
+  const endToken = () => {
+    if (token) tokens.push(token);
+    token = '';
+  };
+  const endLine = () => {
+    endToken();
+    if (tokens.length) {
+      lines.push({
+        tokens: tokens,
+        indented: startsIndented,
+        line: startLine,
+      });
+    }
+    tokens = [];
+  };
+
+  text.split(/\r?\n/).forEach((line, index) => {
+    if (depth == 0 && !quoted) {
+      startsIndented = /^[ \t]/.test(line);
+      startLine = index + 1;
+    }
+    for (let i = 0; i < line.length; i++) {
+      const char = line[i];
+      if (quoted) {
+        token += char;
+        if (char == '\\') {
+          token += line[++i] !== undefined ? line[i] : '';
+        } else if (char == '"') {
+          quoted = false;
+        }
+      } else if (char == '"') {
+        token += char;
+        quoted = true;
+      } else if (char == ';') {
+        break;
+      } else if (char == '(') {
+        endToken();
+        depth++;
+      } else if (char == ')') {
+        endToken();
+        depth = Math.max(0, depth - 1);
+      } else if (char == ' ' || char == '\t') {
+        endToken();
+      } else {
+        token += char;
+      }
+    }
+    endToken();
+    if (depth == 0 && !quoted) endLine();
+  });
+  endLine();
+
+  return lines;
+}
+
+const TTL_UNITS = { s: 1, m: 60, h: 3600, d: 86400, w: 604800 };
+
+// TTLs in seconds, or with units as in 1h30m
+function ttlSeconds(token) {
+  if (/^\d+$/.test(token)) return Number(token);
+  if (!/^(\d+[smhdw])+$/i.test(token)) return null;
+  let seconds = 0;
+  token.replace(/(\d+)([smhdw])/gi, (match, count, unit) => {
+    seconds += Number(count) * TTL_UNITS[unit.toLowerCase()];
+  });
+  return seconds;
+}
+
+function qualify(name, origin) {
+  if (name == '@') return origin;
+  if (name.endsWith('.')) return name;
+  return origin ? name + '.' + origin : name;
+}
+
+function stripDot(name) {
+  return name.length > 1 && name.endsWith('.') ? name.slice(0, -1) : name;
+}
+
+function unquote(token) {
+  return token.replace(/^"(.*)"$/, '$1').replace(/\\(.)/g, '$1');
+}
+
+// Interpret record data the way the Cloudflare API represents it
+function recordContent(type, data) {
+  const record = {};
+  if (type == 'MX' || type == 'SRV' || type == 'URI') {
+    record.priority = Number(data[0]);
+    data = data.slice(1);
+  }
+
+  if (type == 'TXT' || type == 'SPF') {
+    record.content = data.map(unquote).join('');
+  } else if (HOSTNAME_TYPES.includes(type)) {
+    record.content = data
+      .map((part, index) => (index == data.length - 1 ? stripDot(part) : part))
+      .join(' ');
+  } else {
+    record.content = data.join(' ');
+  }
+  return record;
+}
+
+// Parse a BIND zone file into { origin, ttl, records }. Each record has the
+// fully qualified name (without trailing dot), ttl, class, type, the raw data
+// and the content/priority as the Cloudflare API would report them.
+function parseZone(text, defaultOrigin) {
+  let origin = defaultOrigin ? defaultOrigin.replace(/\.?$/, '.') : '';
+  let defaultTtl = null;
+  let lastName = origin;
+  const records = [];
+
+  tokenize(text).forEach((line) => {
+    const tokens = line.tokens.slice();
+    if (tokens[0] == '$ORIGIN') {
+      origin = qualify(tokens[1], origin);
+      return;
+    } else if (tokens[0] == '$TTL') {
+      defaultTtl = ttlSeconds(tokens[1]);
+      return;
+    } else if (tokens[0].startsWith('$')) {
+      return;
+    }
+
+    const name = line.indented ? lastName : qualify(tokens.shift(), origin);
+    lastName = name;
+
+    let ttl = defaultTtl;
+    let recordClass = 'IN';
+    // TTL and class may come in either order before the type
+    for (let i = 0; i < 2 && tokens.length > 1; i++) {
+      if (ttlSeconds(tokens[0]) !== null) {
+        ttl = ttlSeconds(tokens.shift());
+      } else if (CLASSES.includes(tokens[0].toUpperCase())) {
+        recordClass = tokens.shift().toUpperCase();
+      }
+    }
+
+    // A lone TTL or class is what is left when the type is missing
+    if (
+      !tokens.length ||
+      (tokens.length == 1 &&
+        (ttlSeconds(tokens[0]) !== null ||
+          CLASSES.includes(tokens[0].toUpperCase())))
+    ) {
+      throw new Error('line ' + line.line + ': missing record type');
+    }
+    const type = tokens.shift().toUpperCase();
+    records.push(
+      Object.assign(
+        {
+          name: stripDot(name),
+          ttl: ttl,
+          class: recordClass,
+          type: type,
+          data: tokens.join(' '),
+        },
+        recordContent(type, tokens)
+      )
+    );
+  });
+
+  return { origin: stripDot(origin), ttl: defaultTtl, records: records };
+}
+
+// Split zone text into one zone per record type, keyed by type. Directives
+// and the comments heading the file are repeated in each; the comments
+// between records are dropped. Expects one record per line, as Cloudflare
+// exports them.
+function splitZoneText(text, defaultOrigin) {
+  const header = [];
+  const parts = {};
+  let origin = defaultOrigin;
+  let inHeader = true;
+
+  text.split(/\r?\n/).forEach((line) => {
+    const trimmed = line.trim();
+    if (trimmed.startsWith('$')) {
+      if (trimmed.startsWith('$ORIGIN')) origin = trimmed.split(/\s+/)[1];
+      header.push(line);
+      Object.keys(parts).forEach((type) => parts[type].push(line));
+      return;
+    }
+    const records = trimmed.startsWith(';')
+      ? []
+      : parseZone(line, origin).records;
+    if (records.length == 0) {
+      if (inHeader) header.push(line);
+      return;
+    }
+    inHeader = false;
+    const type = records[0].type;
+    if (!parts[type]) parts[type] = header.slice();
+    parts[type].push(line);
+  });
+
+  Object.keys(parts).forEach((type) => {
+    parts[type] = parts[type].join('\n') + '\n';
+  });
+  return parts;
+}
+
+// Order names by their labels from the right, so each name sorts next to its
+// parent and the apex comes first
+function nameKey(name) {
+  return name.toLowerCase().split('.').reverse().join('\u0000');
+}
+
+// Parsed records keep their data as text, API records as content
+function recordData(record) {
+  return typeof record.data == 'string' ? record.data : record.content;
+}
+
+function compareRecords(a, b) {
+  // The SOA leads the zone
+  if ((a.type == 'SOA') != (b.type == 'SOA')) return a.type == 'SOA' ? -1 : 1;
+  const keys = [
+    [nameKey(a.name), nameKey(b.name)],
+    [a.type, b.type],
+    [recordData(a), recordData(b)],
+  ];
+  for (let i = 0; i < keys.length; i++) {
+    if (keys[i][0] < keys[i][1]) return -1;
+    if (keys[i][0] > keys[i][1]) return 1;
+  }
+  return 0;
+}
+
+// A parsed record as one fully qualified zone file line. A record without a
+// TTL is written without one, rather than with a null.
+function recordLine(record) {
+  const hasTtl = record.ttl !== null && record.ttl !== undefined;
+  return [record.name + '.']
+    .concat(hasTtl ? [record.ttl] : [])
+    .concat([record.class, record.type, record.data])
+    .join('\t');
+}
+
+// Re-serialize zone text in a stable form: the comments heading the file,
+// then every record sorted, fully qualified, with its TTL in seconds and
+// single tabs between fields. Records without a TTL get the $TTL, or the SOA
+// minimum as BIND would give them.
+function canonicalZone(text, defaultOrigin) {
+  const header = [];
+  for (const line of text.split(/\r?\n/)) {
+    if (line.trim() != '' && !line.trim().startsWith(';')) break;
+    header.push(line);
+  }
+  const zone = parseZone(text, defaultOrigin);
+  const soa = zone.records.find((record) => record.type == 'SOA');
+  const minimum = soa ? Number(soa.data.split(/\s+/)[6]) : NaN;
+  const defaultTtl =
+    zone.ttl !== null ? zone.ttl : isNaN(minimum) ? null : minimum;
+  const lines = zone.records
+    .map((record) =>
+      record.ttl !== null
+        ? record
+        : Object.assign({}, record, { ttl: defaultTtl })
+    )
+    .sort(compareRecords)
+    .map(recordLine);
+  const directives = ['$ORIGIN ' + zone.origin + '.'];
+  if (zone.ttl !== null) directives.push('$TTL ' + zone.ttl);
+  return header.concat(directives, lines).join('\n') + '\n';
+}
+
+// Drop whole-line comments, such as the timestamped header Cloudflare adds,
+// and the blank lines left at the top
+function stripComments(text) {
+  return text
+    .split(/\r?\n/)
+    .filter((line) => !line.trim().startsWith(';'))
+    .join('\n')
+    .replace(/^\s*\n/, '');
+}
+
+// What Cloudflare keeps about a record that a zone file has no place for,
+// carried as a structured comment after the record, e.g. ; cf: proxied=true
+const ANNOTATIONS = {
+  proxied: (record) => (record.proxied ? true : null),
+  comment: (record) => (record.comment ? record.comment : null),
+  // Tags are joined with commas, as in env:prod,team:web
+  tags: (record) =>
+    record.tags && record.tags.length ? record.tags.join(',') : null,
+};
+
+const ANNOTATION_PREFIX = '; cf:';
+
+// Plain words stay bare, anything else is written as a JSON string
+function annotationValue(value) {
+  const text = String(value);
+  return /^[^\s"=;,]+$/.test(text) ? text : JSON.stringify(text);
+}
+
+function recordAnnotation(record) {
+  const fields = Object.keys(ANNOTATIONS)
+    .map((key) => [key, ANNOTATIONS[key](record)])
+    .filter((pair) => pair[1] !== null && pair[1] !== undefined)
+    .map((pair) => pair[0] + '=' + annotationValue(pair[1]));
+  return fields.length ? ANNOTATION_PREFIX + ' ' + fields.join(' ') : null;
+}
+
+// The fields of a line's structured comment, or null if it has none
+function readAnnotation(line) {
+  const start = line.indexOf(ANNOTATION_PREFIX);
+  if (start == -1) return null;
+  const fields = {};
+  const text = line.slice(start + ANNOTATION_PREFIX.length);
+  const field = /([\w-]+)=("(?:[^"\\]|\\.)*"|[^\s]*)/g;
+  let match;
+  while ((match = field.exec(text))) {
+    const value = match[2].startsWith('"') ? JSON.parse(match[2]) : match[2];
+    if (value === 'true' || value === 'false') {
+      fields[match[1]] = value === 'true';
+    } else {
+      fields[match[1]] = value;
+    }
+  }
+  return fields;
+}
+
+function sameRecord(parsed, record) {
+  return (
+    parsed.name.toLowerCase() == record.name.toLowerCase() &&
+    parsed.type == record.type &&
+    parsed.content == record.content &&
+    (parsed.priority === undefined || parsed.priority == record.priority)
+  );
+}
+
+// Append each API record's annotation to its line of the zone text. Expects
+// one record per line, as Cloudflare exports them.
+function annotateZone(text, defaultOrigin, records) {
+  const unmatched = records.slice();
+  let origin = defaultOrigin;
+  return text
+    .split(/\r?\n/)
+    .map((line) => {
+      const trimmed = line.trim();
+      if (trimmed.startsWith('$ORIGIN')) origin = trimmed.split(/\s+/)[1];
+      if (trimmed.startsWith('$') || trimmed.startsWith(';')) return line;
+      const parsed = parseZone(line, origin).records;
+      if (parsed.length == 0) return line;
+      const index = unmatched.findIndex((record) =>
+        sameRecord(parsed[0], record)
+      );
+      if (index == -1) return line;
+      const annotation = recordAnnotation(unmatched.splice(index, 1)[0]);
+      return annotation ? line + '\t' + annotation : line;
+    })
+    .join('\n');
+}
+
+// Cloudflare's own export marks proxied records with a tag comment
+const PROXIED_TAG = /cf_tags=\S*cf-proxied:true/;
+
+function lineAnnotation(line) {
+  const fields = PROXIED_TAG.test(line) ? { proxied: true } : {};
+  Object.assign(fields, readAnnotation(line));
+  return Object.keys(fields).length ? fields : null;
+}
+
+// Parse zone text as parseZone does, giving each record with a structured
+// comment its fields as annotation, e.g. { proxied: true }. Expects one
+// record per line, as Cloudflare exports them.
+function parseAnnotatedZone(text, defaultOrigin) {
+  const zone = parseZone(text, defaultOrigin);
+  const unmatched = zone.records.slice();
+  let origin = defaultOrigin;
+  text.split(/\r?\n/).forEach((line) => {
+    const trimmed = line.trim();
+    if (trimmed.startsWith('$ORIGIN')) origin = trimmed.split(/\s+/)[1];
+    if (trimmed.startsWith('$') || trimmed.startsWith(';')) return;
+    const annotation = lineAnnotation(line);
+    const parsed = annotation ? parseZone(line, origin).records : [];
+    if (parsed.length == 0) return;
+    const index = unmatched.findIndex(
+      (record) =>
+        record.name.toLowerCase() == parsed[0].name.toLowerCase() &&
+        record.type == parsed[0].type &&
+        record.data == parsed[0].data
+    );
+    if (index != -1) unmatched.splice(index, 1)[0].annotation = annotation;
+  });
+  return zone;
+}
+
+module.exports = {
+  parseZone,
+  tokenize,
+  unquote,
+  splitZoneText,
+  compareRecords,
+  recordLine,
+  canonicalZone,
+  stripComments,
+  annotateZone,
+  readAnnotation,
+  parseAnnotatedZone,
+};
@@ -0,0 +1,72 @@
+const test = require('node:test');
+const assert = require('assert');
+const zlib = require('zlib');
+const { ARCHIVES } = require('../src/archive');
+
+const mtime = new Date('2024-05-01T06:00:00Z');
+
+// The sum of the header bytes, counting the checksum field as spaces
+function headerSum(header) {
+  let sum = 0;
+  for (let i = 0; i < 512; i++) sum += i >= 148 && i < 156 ? 32 : header[i];
+  return sum;
+}
+
+function field(header, start, length) {
+  return header
+    .slice(start, start + length)
+    .toString()
+    .replace(/[\0 ]+$/, '');
+}
+
+test('tar headers carry a valid checksum, size and time', () => {
+  const contents = Buffer.from('example.com.\t1\tIN\tA\t192.0.2.1\n');
+  const tar = ARCHIVES.tar.build([
+    { name: 'example.com.txt', contents: contents, mtime: mtime },
+  ]);
+  const header = tar.slice(0, 512);
+  assert.strictEqual(field(header, 0, 100), 'example.com.txt');
+  assert.strictEqual(parseInt(field(header, 148, 8), 8), headerSum(header));
+  assert.strictEqual(parseInt(field(header, 124, 12), 8), contents.length);
+  assert.strictEqual(
+    parseInt(field(header, 136, 12), 8),
+    mtime.getTime() / 1000
+  );
+  assert.strictEqual(field(header, 257, 6), 'ustar');
+  assert.deepStrictEqual(tar.slice(512, 512 + contents.length), contents);
+  // Contents are padded to whole blocks, then two empty blocks end it
+  assert.strictEqual(tar.length, 512 * 4);
+});
+
+test('long names go in a pax header', () => {
+  const name = 'domains/' + 'sub.'.repeat(30) + 'example.com.txt';
+  const tar = ARCHIVES.tar.build([
+    { name: name, contents: Buffer.from('x'), mtime: mtime },
+  ]);
+  const pax = tar.slice(0, 512);
+  assert.strictEqual(field(pax, 156, 1), 'x');
+  assert.strictEqual(parseInt(field(pax, 148, 8), 8), headerSum(pax));
+  const size = parseInt(field(pax, 124, 12), 8);
+  const record = tar.slice(512, 512 + size).toString();
+  assert.strictEqual(record, size + ' path=' + name + '\n');
+  const header = tar.slice(1024, 1536);
+  assert.strictEqual(parseInt(field(header, 148, 8), 8), headerSum(header));
+});
+
+test('zip entries inflate back with the standard CRC-32', () => {
+  const contents = Buffer.from('123456789');
+  const zip = ARCHIVES.zip.build([
+    { name: 'a.txt', contents: contents, mtime: mtime },
+  ]);
+  assert.strictEqual(zip.readUInt32LE(0), 0x04034b50);
+  // The check value of CRC-32 for "123456789"
+  assert.strictEqual(zip.readUInt32LE(14), 0xcbf43926);
+  const compressed = zip.readUInt32LE(18);
+  const nameLength = zip.readUInt16LE(26);
+  assert.strictEqual(zip.slice(30, 30 + nameLength).toString(), 'a.txt');
+  const data = zip.slice(30 + nameLength, 30 + nameLength + compressed);
+  assert.deepStrictEqual(zlib.inflateRawSync(data), contents);
+  const end = zip.slice(zip.length - 22);
+  assert.strictEqual(end.readUInt32LE(0), 0x06054b50);
+  assert.strictEqual(end.readUInt16LE(10), 1);
+});
@@ -0,0 +1,77 @@
+const test = require('node:test');
+const assert = require('assert');
+const { parseArgs, configValue } = require('../src/cli');
+
+test('export is the command when none is given', () => {
+  assert.deepStrictEqual(parseArgs(['--format', 'json', '-q']), {
+    command: 'export',
+    options: { format: 'json', quiet: true },
+    positionals: [],
+  });
+});
+
+test('commands take their arguments and options', () => {
+  const parsed = parseArgs([
+    'sync',
+    'example.com',
+    'example.com.txt',
+    '--prune',
+    '--concurrency=8',
+    '-y',
+  ]);
+  assert.strictEqual(parsed.command, 'sync');
+  assert.deepStrictEqual(parsed.positionals, [
+    'example.com',
+    'example.com.txt',
+  ]);
+  assert.deepStrictEqual(parsed.options, {
+    prune: true,
+    concurrency: 8,
+    yes: true,
+  });
+});
+
+test('bundled short flags count, -- ends the options', () => {
+  const parsed = parseArgs(['-vv', 'export', '--', '--not-a-flag']);
+  assert.strictEqual(parsed.command, 'export');
+  assert.strictEqual(parsed.options.verbose, 2);
+  assert.deepStrictEqual(parsed.positionals, ['--not-a-flag']);
+});
+
+test('flags of other commands are rejected', () => {
+  assert.throws(
+    () => parseArgs(['list', '--prune']),
+    /Option --prune is not valid for list/
+  );
+  assert.throws(
+    () => parseArgs(['export', '--format', 'docx']),
+    /--format must be one of/
+  );
+  assert.throws(() => parseArgs(['--nope']), /Unknown option --nope/);
+  assert.throws(() => parseArgs(['-Z']), /Unknown option -Z/);
+});
+
+test('values are checked against their option', () => {
+  assert.throws(
+    () => parseArgs(['--concurrency', '0']),
+    /expects a whole number of at least 1/
+  );
+  assert.throws(() => parseArgs(['--timeout']), /requires a value/);
+});
+
+test('argument counts and choices are checked', () => {
+  assert.throws(
+    () => parseArgs(['list', 'extra']),
+    /Unexpected argument "extra"/
+  );
+  assert.throws(
+    () => parseArgs(['credentials', 'show']),
+    /Invalid action "show", expected store or clear/
+  );
+});
+
+test('config file values are checked like flags', () => {
+  assert.strictEqual(configValue('concurrency', 8), 8);
+  assert.strictEqual(configValue('zones', ['a.com', 'b.com']), 'a.com,b.com');
+  assert.throws(() => configValue('quiet', 'yes'), /quiet must be true/);
+});
@@ -0,0 +1,31 @@
+const test = require('node:test');
+const assert = require('assert');
+const { runConcurrently } = require('../src/concurrency');
+
+const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms));
+
+test('runs at most limit items at once', async () => {
+  let running = 0;
+  let most = 0;
+  await runConcurrently([1, 2, 3, 4, 5], 2, async () => {
+    running++;
+    most = Math.max(most, running);
+    await sleep(5);
+    running--;
+  });
+  assert.strictEqual(most, 2);
+});
+
+test('a failure waits for the items in flight and starts no more', async () => {
+  const finished = [];
+  await assert.rejects(
+    runConcurrently([1, 2, 3, 4], 2, async (item) => {
+      await sleep(item == 1 ? 5 : 20);
+      if (item == 1) throw new Error('zone 1 failed');
+      finished.push(item);
+    }),
+    /zone 1 failed/
+  );
+  // Item 2 was already running when item 1 failed, items 3 and 4 never start
+  assert.deepStrictEqual(finished, [2]);
+});
@@ -0,0 +1,50 @@
+const test = require('node:test');
+const assert = require('assert');
+const { parseToml } = require('../src/config');
+
+test('tables, dotted and quoted keys', () => {
+  const config = parseToml(
+    'default_profile = "prod"\n' +
+      '\n' +
+      '[profiles.prod]\n' +
+      'api_token_file = "/run/secrets/cf"\n' +
+      '\n' +
+      '[profiles."eu.example.com"]\n' +
+      "zones.'example.com'.format = 'json'\n"
+  );
+  assert.deepStrictEqual(config, {
+    default_profile: 'prod',
+    profiles: {
+      prod: { api_token_file: '/run/secrets/cf' },
+      'eu.example.com': { zones: { 'example.com': { format: 'json' } } },
+    },
+  });
+});
+
+test('strings, numbers, booleans, arrays and comments', () => {
+  const config = parseToml(
+    '# top\n' +
+      'path = "C:\\\\exports\\t\\"quoted\\" # not a comment"\n' +
+      "literal = 'no \\escapes'\n" +
+      'equals = "a = b" # trailing comment\n' +
+      'concurrency = 1_000\n' +
+      'ratio = -0.5\n' +
+      'quiet = true\n' +
+      'zones = ["a.com", \'b.com\' , "c=d"]\n'
+  );
+  assert.deepStrictEqual(config, {
+    path: 'C:\\exports\t"quoted" # not a comment',
+    literal: 'no \\escapes',
+    equals: 'a = b',
+    concurrency: 1000,
+    ratio: -0.5,
+    quiet: true,
+    zones: ['a.com', 'b.com', 'c=d'],
+  });
+});
+
+test('errors give the line number', () => {
+  assert.throws(() => parseToml('a = 1\nb = nope\n'), /line 2: unsupported/);
+  assert.throws(() => parseToml('a = "x" y\n'), /line 1: unexpected y/);
+  assert.throws(() => parseToml('just a key\n'), /line 1: expected key/);
+});
@@ -0,0 +1,71 @@
+// A zone as the dns_records API returns it, for the record-based formats
+function sampleZone() {
+  return {
+    id: '023e105f4ecef8ad9ca31a8372d0c353',
+    name: 'example.com',
+    records: [
+      {
+        id: 'r1',
+        type: 'A',
+        name: 'example.com',
+        content: '192.0.2.1',
+        ttl: 1,
+        proxied: true,
+        proxiable: true,
+        comment: 'Main site',
+        tags: ['env:prod'],
+      },
+      {
+        id: 'r2',
+        type: 'MX',
+        name: 'example.com',
+        content: 'mail.example.com',
+        ttl: 300,
+        priority: 10,
+        proxied: false,
+      },
+      {
+        id: 'r3',
+        type: 'TXT',
+        name: 'example.com',
+        content: 'v=spf1 include:_spf.example.com -all',
+        ttl: 3600,
+        proxied: false,
+      },
+      {
+        id: 'r4',
+        type: 'CNAME',
+        name: 'www.example.com',
+        content: 'example.com',
+        ttl: 1,
+        proxied: true,
+        proxiable: true,
+      },
+      {
+        id: 'r5',
+        type: 'SRV',
+        name: '_sip._tcp.example.com',
+        content: '5 5060 sip.example.com',
+        ttl: 3600,
+        priority: 10,
+        proxied: false,
+        data: {
+          priority: 10,
+          weight: 5,
+          port: 5060,
+          target: 'sip.example.com',
+        },
+      },
+      {
+        id: 'r6',
+        type: 'NS',
+        name: 'example.com',
+        content: 'ns.cloudflare.com',
+        ttl: 86400,
+        proxied: false,
+      },
+    ],
+  };
+}
+
+module.exports = { sampleZone };
@@ -0,0 +1,19 @@
+const test = require('node:test');
+const assert = require('assert');
+const bicep = require('../../src/formats/azure-bicep');
+const { sampleZone } = require('../fixtures/zone');
+
+test('long TXT values are split into strings of 255 characters', () => {
+  const zone = sampleZone();
+  zone.records = [
+    Object.assign(zone.records[2], { content: 'a'.repeat(255) + 'b' }),
+  ];
+  const value =
+    '        value: [\n' +
+    "          '" +
+    'a'.repeat(255) +
+    "'\n" +
+    "          'b'\n" +
+    '        ]\n';
+  assert.ok(bicep.render(zone).includes(value));
+});
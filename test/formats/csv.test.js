@@ -0,0 +1,24 @@
+const test = require('node:test');
+const assert = require('assert');
+const csv = require('../../src/formats/csv');
+const { sampleZone } = require('../fixtures/zone');
+
+test('a row per record under a header', () => {
+  const lines = csv.render(sampleZone()).split('\r\n');
+  assert.strictEqual(
+    lines[0],
+    'zone,type,name,content,ttl,priority,proxied,id'
+  );
+  assert.strictEqual(
+    lines[2],
+    'example.com,MX,example.com,mail.example.com,300,10,false,r2'
+  );
+  assert.strictEqual(lines.length, 8);
+});
+
+test('fields with commas, quotes or newlines are quoted', () => {
+  assert.strictEqual(
+    csv.csvRows([['a,b', 'say "hi"', 'two\nlines', 'plain']]),
+    '"a,b","say ""hi""","two\nlines",plain\r\n'
+  );
+});
@@ -0,0 +1,31 @@
+const test = require('node:test');
+const assert = require('assert');
+const hosts = require('../../src/formats/hosts');
+const { sampleZone } = require('../fixtures/zone');
+
+const zone = () => {
+  const zone = sampleZone();
+  zone.records = [
+    { type: 'A', name: 'example.com', content: '192.0.2.1' },
+    { type: 'A', name: 'www.example.com', content: '192.0.2.1' },
+    { type: 'AAAA', name: 'a.dev.example.com', content: '2001:db8::1' },
+    { type: 'A', name: '*.example.com', content: '192.0.2.2' },
+  ];
+  return zone;
+};
+
+test('each address is listed once with the names that resolve to it', () => {
+  assert.strictEqual(
+    hosts.render(zone(), {}),
+    '# example.com\n' +
+      '192.0.2.1\texample.com www.example.com\n' +
+      '2001:db8::1\ta.dev.example.com\n'
+  );
+});
+
+test('--hosts-subdomain comes from the render options', () => {
+  assert.strictEqual(
+    hosts.render(zone(), { hostsSubdomain: 'dev' }),
+    '# example.com\n2001:db8::1\ta.dev.example.com\n'
+  );
+});
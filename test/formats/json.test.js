@@ -0,0 +1,23 @@
+const test = require('node:test');
+const assert = require('assert');
+const json = require('../../src/formats/json');
+const { sampleZone } = require('../fixtures/zone');
+
+test('records keep every API field, with comment and tags always set', () => {
+  const zone = sampleZone();
+  zone.records[0].zone_id = zone.id;
+  zone.records[0].zone_name = zone.name;
+  const document = JSON.parse(json.render(zone));
+  assert.strictEqual(document.zone, 'example.com');
+  assert.strictEqual(document.id, zone.id);
+  assert.strictEqual(document.records[0].zone_id, undefined);
+  assert.strictEqual(document.records[0].comment, 'Main site');
+  assert.strictEqual(document.records[1].comment, null);
+  assert.deepStrictEqual(document.records[1].tags, []);
+  assert.deepStrictEqual(document.records[4].data, zone.records[4].data);
+});
+
+test('--single-file writes a list of zones', () => {
+  const zones = JSON.parse(json.renderAll([sampleZone(), sampleZone()]));
+  assert.strictEqual(zones.length, 2);
+});
@@ -0,0 +1,51 @@
+const test = require('node:test');
+const assert = require('assert');
+const route53 = require('../../src/formats/route53');
+const { sampleZone } = require('../fixtures/zone');
+
+test('records become UPSERTs of record sets, without the apex NS', () => {
+  const batch = JSON.parse(route53.render(sampleZone()));
+  assert.strictEqual(batch.Comment, 'Exported from Cloudflare');
+  assert.deepStrictEqual(
+    batch.Changes.map((change) => change.ResourceRecordSet),
+    [
+      {
+        Name: 'example.com.',
+        Type: 'A',
+        TTL: 300,
+        ResourceRecords: [{ Value: '192.0.2.1' }],
+      },
+      {
+        Name: 'example.com.',
+        Type: 'MX',
+        TTL: 300,
+        ResourceRecords: [{ Value: '10 mail.example.com.' }],
+      },
+      {
+        Name: 'example.com.',
+        Type: 'TXT',
+        TTL: 3600,
+        ResourceRecords: [{ Value: '"v=spf1 include:_spf.example.com -all"' }],
+      },
+      {
+        Name: 'www.example.com.',
+        Type: 'CNAME',
+        TTL: 300,
+        ResourceRecords: [{ Value: 'example.com.' }],
+      },
+      {
+        Name: '_sip._tcp.example.com.',
+        Type: 'SRV',
+        TTL: 3600,
+        ResourceRecords: [{ Value: '10 5 5060 sip.example.com.' }],
+      },
+    ]
+  );
+});
+
+test('long TXT values are split into strings of 255 characters', () => {
+  assert.strictEqual(
+    route53.VALUE_TYPES.TXT({ content: 'a'.repeat(300) }),
+    '"' + 'a'.repeat(255) + '" "' + 'a'.repeat(45) + '"'
+  );
+});
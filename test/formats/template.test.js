@@ -0,0 +1,21 @@
+const test = require('node:test');
+const assert = require('assert');
+const template = require('../../src/formats/template');
+const { compileTemplate } = require('../../src/template');
+const { renderZones } = require('../../src/formats');
+const { sampleZone } = require('../fixtures/zone');
+
+test('every zone has the time the run started as exported_at', () => {
+  template.template = compileTemplate(
+    '{{name}} {{exported_at}}{{#records}} {{type}}{{/records}}'
+  );
+  const other = sampleZone();
+  other.name = 'example.org';
+  other.records = [];
+  const options = { exportedAt: new Date('2024-01-02T03:04:05.678Z') };
+  assert.strictEqual(
+    renderZones(template, [sampleZone(), other], options),
+    'example.com 2024-01-02T03:04:05Z A MX TXT CNAME SRV NS\n\n' +
+      'example.org 2024-01-02T03:04:05Z\n'
+  );
+});
@@ -0,0 +1,41 @@
+const test = require('node:test');
+const assert = require('assert');
+const terraform = require('../../src/formats/terraform');
+const { sampleZone } = require('../fixtures/zone');
+
+test('a resource and import block per record Terraform can manage', () => {
+  const text = terraform.render(sampleZone());
+  assert.match(
+    text,
+    /resource "cloudflare_record" "www_example_com_cname" \{\n {2}zone_id = "023e105f4ecef8ad9ca31a8372d0c353"\n {2}name {4}= "www.example.com"\n/
+  );
+  assert.match(text, / {2}to = cloudflare_record.example_com_mx\n/);
+  // Cloudflare manages the apex NS records itself
+  assert.doesNotMatch(text, /"NS"/);
+});
+
+test('structured records get a data block rather than content', () => {
+  const resource = terraform.recordArguments(sampleZone().records[4]);
+  assert.deepStrictEqual(
+    resource.args.map((pair) => pair[0]),
+    ['name', 'type', 'ttl']
+  );
+  assert.deepStrictEqual(resource.data[3], ['target', 'sip.example.com']);
+});
+
+test('template sequences are escaped', () => {
+  assert.strictEqual(
+    terraform.hclString('${var} %{if} "q"'),
+    '"$${var} %%{if} \\"q\\""'
+  );
+});
+
+test('resource names are unique and valid identifiers', () => {
+  const used = new Set();
+  const record = { name: '1.example.com', type: 'A' };
+  assert.strictEqual(terraform.resourceName(record, used), '_1_example_com_a');
+  assert.strictEqual(
+    terraform.resourceName(record, used),
+    '_1_example_com_a_2'
+  );
+});
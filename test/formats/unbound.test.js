@@ -0,0 +1,18 @@
+const test = require('node:test');
+const assert = require('assert');
+const unbound = require('../../src/formats/unbound');
+const { sampleZone } = require('../fixtures/zone');
+
+test('local-data is double quoted, escaping quotes and backslashes', () => {
+  const zone = sampleZone();
+  zone.records = [
+    Object.assign(zone.records[2], { content: 'it\'s "quoted" \\ here' }),
+  ];
+  assert.strictEqual(
+    unbound.render(zone),
+    'server:\n' +
+      '  local-zone: "example.com." transparent\n' +
+      '  local-data: "example.com. 3600 IN TXT ' +
+      '\\"it\'s \\\\\\"quoted\\\\\\" \\\\\\\\ here\\""\n'
+  );
+});
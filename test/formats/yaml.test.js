@@ -0,0 +1,102 @@
+const test = require('node:test');
+const assert = require('assert');
+const yaml = require('../../src/formats/yaml');
+const { sampleZone } = require('../fixtures/zone');
+
+test('records are written as a list of mappings', () => {
+  const zone = sampleZone();
+  zone.records = zone.records.slice(1, 2);
+  assert.strictEqual(
+    yaml.render(zone),
+    'zone: example.com\n' +
+      'id: 023e105f4ecef8ad9ca31a8372d0c353\n' +
+      'records:\n' +
+      '  - id: r2\n' +
+      '    type: MX\n' +
+      '    name: example.com\n' +
+      '    content: mail.example.com\n' +
+      '    ttl: 300\n' +
+      '    priority: 10\n' +
+      '    proxied: false\n' +
+      '    comment: null\n' +
+      '    tags: []\n'
+  );
+});
+
+test('strings YAML would read differently are quoted', () => {
+  const quoted = {
+    colon: 'v=DKIM1; k=rsa: p',
+    hash: 'a #comment',
+    bool: 'yes',
+    number: '300',
+    dash: '-x',
+    trailing: 'space ',
+    leading: ' space',
+  };
+  Object.keys(quoted).forEach((key) => {
+    assert.strictEqual(
+      yaml.toYaml({ value: quoted[key] }),
+      'value: ' + JSON.stringify(quoted[key]) + '\n'
+    );
+  });
+  assert.strictEqual(
+    yaml.toYaml({ value: 'v=spf1 -all' }),
+    'value: v=spf1 -all\n'
+  );
+});
+
+test('line breaks and control characters are escaped on one line', () => {
+  assert.strictEqual(
+    yaml.toYaml({ comment: 'two\nlines', txt: 'tab\there', cr: 'a\r' }),
+    'comment: "two\\nlines"\ntxt: "tab\\there"\ncr: "a\\r"\n'
+  );
+});
+
+test('exports read back as they were written', () => {
+  const zone = {
+    zone: 'example.com',
+    id: '023e105f4ecef8ad9ca31a8372d0c353',
+    records: [
+      {
+        name: 'example.com',
+        type: 'TXT',
+        content: 'v=spf1 include:"_spf.example.com" -all # not a comment',
+        ttl: 1,
+        proxied: false,
+        comment: null,
+        tags: [],
+      },
+      {
+        name: '*.example.com',
+        type: 'A',
+        content: '192.0.2.1',
+        ttl: 300,
+        proxied: true,
+        comment: 'yes',
+        tags: ['env:prod', 'team: web'],
+      },
+      {
+        name: 'example.com',
+        type: 'CAA',
+        content: '0 issue "letsencrypt.org"',
+        data: { flags: 0, tag: 'issue', value: 'letsencrypt.org' },
+        comment: '- dash: and "quotes"\nand a newline',
+        tags: [],
+      },
+    ],
+  };
+  assert.deepStrictEqual(yaml.fromYaml(yaml.toYaml(zone)), zone);
+});
+
+test('strings that look like other values stay strings', () => {
+  const values = { a: 'true', b: '300', c: 'null', d: '', e: '~', f: '0x1f' };
+  assert.deepStrictEqual(yaml.fromYaml(yaml.toYaml(values)), values);
+});
+
+test('a list of zones, as --single-file writes', () => {
+  const zones = [
+    { zone: 'a.com', records: [{ name: 'a.com', ttl: 1 }] },
+    { zone: 'b.com', records: [] },
+  ];
+  assert.deepStrictEqual(yaml.fromYaml(yaml.toYaml(zones)), zones);
+});
@@ -0,0 +1,55 @@
+const test = require('node:test');
+const assert = require('assert');
+const { registerSecret, redact, wrapStream } = require('../src/redact');
+
+// A stream that keeps what was written to it
+function capture() {
+  const chunks = [];
+  return {
+    chunks: chunks,
+    write(chunk) {
+      chunks.push(chunk);
+      return true;
+    },
+  };
+}
+
+test('registered secrets and credential variables are masked', () => {
+  registerSecret('registered-secret-value');
+  process.env.CLOUDFLARE_API_TOKEN = 'token-from-the-env';
+  try {
+    assert.strictEqual(
+      redact('a registered-secret-value b token-from-the-env c'),
+      'a [REDACTED] b [REDACTED] c'
+    );
+  } finally {
+    delete process.env.CLOUDFLARE_API_TOKEN;
+  }
+});
+
+test('placeholders and short values are left alone', () => {
+  registerSecret('abc');
+  process.env.CLOUDFLARE_API_KEY = 'NULL';
+  try {
+    assert.strictEqual(redact('abc NULL'), 'abc NULL');
+  } finally {
+    delete process.env.CLOUDFLARE_API_KEY;
+  }
+});
+
+test('text writes are redacted and buffers pass through untouched', () => {
+  registerSecret('stream-secret-value');
+  const stream = capture();
+  wrapStream(stream);
+  stream.write('Error: stream-secret-value rejected\n');
+  const binary = Buffer.from([0x1f, 0x8b, 0xff, 0xfe]);
+  const withSecret = Buffer.concat([
+    binary,
+    Buffer.from('stream-secret-value'),
+  ]);
+  stream.write(binary);
+  stream.write(withSecret);
+  assert.strictEqual(stream.chunks[0], 'Error: [REDACTED] rejected\n');
+  assert.strictEqual(stream.chunks[1], binary);
+  assert.strictEqual(stream.chunks[2], withSecret);
+});
@@ -0,0 +1,226 @@
+const test = require('node:test');
+const assert = require('assert');
+const {
+  planChanges,
+  planDocument,
+  readPlan,
+  readSnapshot,
+  recordsDigest,
+  rewriteRecord,
+} = require('../src/restore');
+const { toYaml } = require('../src/formats/yaml');
+
+const zone = (lines) =>
+  readSnapshot(
+    '$ORIGIN example.com.\n' + lines.join('\n') + '\n',
+    'example.com.txt',
+    'example.com'
+  );
+
+test('structured types are sent as data', () => {
+  const payloads = zone([
+    '@ 300 IN CAA 0 issue "letsencrypt.org"',
+    '_sip._tcp 300 IN SRV 10 5 5060 sip.example.com.',
+    '@ 300 IN DS 2371 13 2 1F987CC6583E92DF0890718C42 A4F4E8',
+    '_443._tcp 300 IN TLSA 3 1 1 0C72AC70B745AC19998811B131D662C9',
+    '@ 300 IN NAPTR 100 10 "U" "E2U+sip" "!^.*$!sip:info@example.com!" .',
+    '_http._tcp 300 IN URI 10 1 "https://example.com/"',
+    '@ 300 IN HTTPS 1 . alpn="h3,h2"',
+  ]);
+  assert.deepStrictEqual(
+    payloads.map((payload) => payload.data),
+    [
+      { flags: 0, tag: 'issue', value: 'letsencrypt.org' },
+      { priority: 10, weight: 5, port: 5060, target: 'sip.example.com' },
+      {
+        key_tag: 2371,
+        algorithm: 13,
+        digest_type: 2,
+        digest: '1F987CC6583E92DF0890718C42A4F4E8',
+      },
+      {
+        usage: 3,
+        selector: 1,
+        matching_type: 1,
+        certificate: '0C72AC70B745AC19998811B131D662C9',
+      },
+      {
+        order: 100,
+        preference: 10,
+        flags: 'U',
+        service: 'E2U+sip',
+        regex: '!^.*$!sip:info@example.com!',
+        replacement: '.',
+      },
+      { weight: 1, target: 'https://example.com/' },
+      { priority: 1, target: '.', value: 'alpn="h3,h2"' },
+    ]
+  );
+  assert.strictEqual(payloads[5].priority, 10);
+  assert.ok(payloads.every((payload) => payload.content === undefined));
+});
+
+test('LOC fills in the parts RFC 1876 leaves optional', () => {
+  const payloads = zone(['@ 300 IN LOC 52 22 23.000 N 4 53 32.000 E -2.00m']);
+  assert.deepStrictEqual(payloads[0].data, {
+    lat_degrees: 52,
+    lat_minutes: 22,
+    lat_seconds: 23,
+    lat_direction: 'N',
+    long_degrees: 4,
+    long_minutes: 53,
+    long_seconds: 32,
+    long_direction: 'E',
+    altitude: -2,
+    size: 1,
+    precision_horz: 10000,
+    precision_vert: 10,
+  });
+});
+
+test('malformed structured records are refused by name', () => {
+  assert.throws(() => zone(['@ 300 IN DS 2371 13']), /Invalid DS record/);
+  assert.throws(() => zone(['@ 300 IN SSHFP x 2 ab']), /Invalid SSHFP record/);
+});
+
+test('the SOA and apex NS records are left to Cloudflare', () => {
+  const payloads = zone([
+    '@ 3600 IN SOA ns.cloudflare.com. dns.cloudflare.com. 1 2 3 4 5',
+    '@ 86400 IN NS ns.cloudflare.com.',
+    'sub 300 IN NS ns1.example.net.',
+  ]);
+  assert.deepStrictEqual(
+    payloads.map((payload) => payload.name + ' ' + payload.type),
+    ['sub.example.com NS']
+  );
+});
+
+test('records outside the zone are refused', () => {
+  assert.throws(
+    () =>
+      readSnapshot('other.org. 300 IN A 192.0.2.1\n', 'x.txt', 'example.com'),
+    /Not in example.com/
+  );
+});
+
+test("Cloudflare's cf-proxied tag marks a record as proxied", () => {
+  const payloads = zone([
+    'www 1 IN CNAME example.com. ; cf_tags=cf-proxied:true',
+    'mail 1 IN CNAME example.com.',
+  ]);
+  assert.strictEqual(payloads[0].proxied, true);
+  assert.strictEqual(payloads[1].proxied, false);
+});
+
+test('a record without a type is refused with its line', () => {
+  assert.throws(() => zone(['www 300 IN']), /line 2: missing record type/);
+  assert.throws(() => zone(['', 'www']), /line 3: missing record type/);
+});
+
+const existing = () => [
+  { id: '1', name: 'example.com', type: 'A', content: '192.0.2.1', ttl: 300 },
+  { id: '2', name: 'www.example.com', type: 'A', content: '192.0.2.2' },
+  { id: '3', name: 'old.example.com', type: 'A', content: '192.0.2.3' },
+  { id: '4', name: 'gone.example.com', type: 'TXT', content: 'x' },
+  { id: '5', name: 'example.com', type: 'NS', content: 'ns.cloudflare.com' },
+];
+
+const snapshot = () =>
+  zone([
+    '@ 300 IN A 192.0.2.1',
+    'www 600 IN A 192.0.2.2',
+    'old 1 IN A 192.0.2.30',
+    'new 1 IN A 192.0.2.4',
+  ]);
+
+const actions = (changes) =>
+  changes.map((change) =>
+    [change.action]
+      .concat(change.record ? [change.record.id] : [])
+      .concat(change.payload ? [change.payload.name] : [])
+      .join(' ')
+  );
+
+test('sync keeps, updates, replaces and deletes records to match', () => {
+  assert.deepStrictEqual(
+    actions(planChanges(snapshot(), existing(), 'example.com', true)),
+    [
+      'delete 4',
+      'update 2 www.example.com',
+      'update 3 old.example.com',
+      'create new.example.com',
+    ]
+  );
+});
+
+test('without prune sync only adds records', () => {
+  assert.deepStrictEqual(
+    actions(planChanges(snapshot(), existing(), 'example.com', false)),
+    [
+      'update 2 www.example.com',
+      'create old.example.com',
+      'create new.example.com',
+    ]
+  );
+});
+
+test("a saved plan's digest tells whether the zone changed since", () => {
+  const domain = { id: 'z1', name: 'example.com' };
+  const changes = planChanges(snapshot(), existing(), 'example.com', true);
+  const plan = readPlan(
+    JSON.stringify(planDocument(domain, 'example.com.txt', changes, existing()))
+  );
+  assert.deepStrictEqual(plan.changes, changes);
+  // The order the API lists records in does not matter
+  assert.strictEqual(recordsDigest(existing().reverse()), plan.records_digest);
+  const drifted = existing();
+  drifted[1].ttl = 120;
+  assert.notStrictEqual(recordsDigest(drifted), plan.records_digest);
+  assert.throws(() => readPlan('{"version": 0}'), /Not a plan file/);
+});
+
+test('json and yaml exports are read as snapshots of their zone', () => {
+  const zones = [
+    { zone: 'other.org', records: [] },
+    {
+      zone: 'example.com',
+      records: [
+        {
+          name: 'example.com',
+          type: 'MX',
+          content: 'mail.example.com',
+          priority: 10,
+          ttl: 300,
+          proxied: false,
+        },
+      ],
+    },
+  ];
+  const json = readSnapshot(JSON.stringify(zones), 'x.json', 'example.com');
+  const yaml = readSnapshot(toYaml(zones), 'x.yaml', 'example.com');
+  assert.deepStrictEqual(yaml, json);
+  assert.strictEqual(json[0].priority, 10);
+  assert.throws(
+    () => readSnapshot('[]', 'x.json', 'example.com'),
+    /No records of example.com/
+  );
+});
+
+test('rewrites change whole names only', () => {
+  const rewrites = [['example.com', 'example.net']];
+  const record = (name, content) =>
+    rewriteRecord({ name: name, type: 'CNAME', content: content }, rewrites);
+  assert.deepStrictEqual(record('www.example.com', 'example.com.'), {
+    name: 'www.example.net',
+    type: 'CNAME',
+    content: 'example.net.',
+  });
+  assert.strictEqual(
+    record('a.example.com.au', 'myexample.com').name,
+    'a.example.com.au'
+  );
+  assert.strictEqual(
+    record('a.example.com.au', 'myexample.com').content,
+    'myexample.com'
+  );
+});
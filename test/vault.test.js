@@ -0,0 +1,61 @@
+const test = require('node:test');
+const assert = require('assert');
+const axios = require('axios');
+const { readVaultToken } = require('../src/vault');
+
+// Answers the mount lookup with the given KV version, or refuses it, and
+// records the secrets read
+function vault(version) {
+  const read = [];
+  axios.get = async (url) => {
+    if (url.startsWith('v1/sys/internal/ui/mounts/')) {
+      if (!version) throw new Error('permission denied');
+      return {
+        data: { data: { path: 'secret/', options: { version: version } } },
+      };
+    }
+    read.push(url);
+    return url.startsWith('v1/secret/data/')
+      ? { data: { data: { data: { token: 'kv2' }, metadata: {} } } }
+      : { data: { data: { token: 'kv1' } } };
+  };
+  return read;
+}
+
+const env = (extra) =>
+  Object.assign(
+    {
+      VAULT_ADDR: 'https://vault.example.com:8200',
+      VAULT_TOKEN: 'vt',
+      CLOUDFLARE_VAULT_PATH: 'secret/cloudflare',
+    },
+    extra
+  );
+
+test("the KV version comes from the secret's mount", async () => {
+  let read = vault('2');
+  assert.strictEqual(await readVaultToken(env()), 'kv2');
+  assert.deepStrictEqual(read, ['v1/secret/data/cloudflare']);
+
+  read = vault('2');
+  const withData = env({ CLOUDFLARE_VAULT_PATH: 'secret/data/cloudflare' });
+  assert.strictEqual(await readVaultToken(withData), 'kv2');
+  assert.deepStrictEqual(read, ['v1/secret/data/cloudflare']);
+
+  read = vault('1');
+  assert.strictEqual(await readVaultToken(env()), 'kv1');
+  assert.deepStrictEqual(read, ['v1/secret/cloudflare']);
+});
+
+test('an explicit KV version is used when the mount is unknown', async () => {
+  vault(null);
+  assert.strictEqual(
+    await readVaultToken(env({ CLOUDFLARE_VAULT_KV_VERSION: '2' })),
+    'kv2'
+  );
+  vault(null);
+  await assert.rejects(
+    readVaultToken(env({ CLOUDFLARE_VAULT_PATH: 'secret/data/cloudflare' })),
+    /--vault-kv-version 2/
+  );
+});
@@ -0,0 +1,112 @@
+const test = require('node:test');
+const assert = require('assert');
+const {
+  annotateZone,
+  canonicalZone,
+  parseAnnotatedZone,
+  parseZone,
+  tokenize,
+  unquote,
+} = require('../src/zonefile');
+
+test('quoted strings keep their spaces, semicolons and escapes', () => {
+  const lines = tokenize('txt IN TXT "a b; c" "say \\"hi\\"" ; comment\n');
+  assert.deepStrictEqual(lines[0].tokens, [
+    'txt',
+    'IN',
+    'TXT',
+    '"a b; c"',
+    '"say \\"hi\\""',
+  ]);
+  assert.strictEqual(unquote('"say \\"hi\\""'), 'say "hi"');
+});
+
+test('TXT strings are joined and unquoted as the API reports them', () => {
+  const zone = parseZone(
+    '$ORIGIN example.com.\n' +
+      'txt 300 IN TXT "v=DKIM1; k=rsa; " "p=MIGf\\\\Ma"\n',
+    'example.com'
+  );
+  assert.strictEqual(zone.records[0].name, 'txt.example.com');
+  assert.strictEqual(zone.records[0].content, 'v=DKIM1; k=rsa; p=MIGf\\Ma');
+});
+
+test('parentheses continue a record over several lines', () => {
+  const zone = parseZone(
+    '$TTL 1h\n' +
+      '@ IN SOA ns.example.com. admin.example.com. (\n' +
+      '  2024010101 ; serial\n' +
+      '  7200 3600 1209600 300 )\n' +
+      '  IN MX 10 mail.example.com.\n',
+    'example.com'
+  );
+  assert.strictEqual(zone.records.length, 2);
+  assert.strictEqual(zone.records[0].ttl, 3600);
+  assert.strictEqual(
+    zone.records[0].data,
+    'ns.example.com. admin.example.com. 2024010101 7200 3600 1209600 300'
+  );
+  assert.strictEqual(zone.records[1].name, 'example.com');
+  assert.strictEqual(zone.records[1].priority, 10);
+  assert.strictEqual(zone.records[1].content, 'mail.example.com');
+});
+
+test('canonical zones sort records and fill in missing TTLs', () => {
+  const text = canonicalZone(
+    '; header\n' +
+      'www IN A 192.0.2.1\n' +
+      '@ IN SOA ns.example.com. admin.example.com. 1 7200 3600 1209600 300\n',
+    'example.com'
+  );
+  assert.strictEqual(
+    text,
+    '; header\n' +
+      '$ORIGIN example.com.\n' +
+      'example.com.\t300\tIN\tSOA\t' +
+      'ns.example.com. admin.example.com. 1 7200 3600 1209600 300\n' +
+      'www.example.com.\t300\tIN\tA\t192.0.2.1\n'
+  );
+  assert.strictEqual(
+    canonicalZone('www IN A 192.0.2.1\n', 'example.com'),
+    '$ORIGIN example.com.\nwww.example.com.\tIN\tA\t192.0.2.1\n'
+  );
+});
+
+test('proxied annotations round trip', () => {
+  const text =
+    '$ORIGIN example.com.\n' +
+    'example.com.\t1\tIN\tA\t192.0.2.1\n' +
+    'www.example.com.\t1\tIN\tCNAME\texample.com.\n';
+  const records = [
+    { name: 'example.com', type: 'A', content: '192.0.2.1', proxied: true },
+    { name: 'www.example.com', type: 'CNAME', content: 'example.com' },
+  ];
+  const annotated = annotateZone(text, 'example.com', records);
+  const parsed = parseAnnotatedZone(annotated, 'example.com').records;
+  assert.deepStrictEqual(parsed[0].annotation, { proxied: true });
+  assert.strictEqual(parsed[0].content, '192.0.2.1');
+  assert.strictEqual(parsed[1].annotation, undefined);
+});
+
+test('comments and tags round trip, including quoted comments', () => {
+  const text =
+    '$ORIGIN example.com.\nexample.com.\t1\tIN\tA\t192.0.2.1\n';
+  const records = [
+    {
+      name: 'example.com',
+      type: 'A',
+      content: '192.0.2.1',
+      proxied: true,
+      comment: 'Main site, "prod"; do not remove',
+      tags: ['env:prod', 'team:web'],
+    },
+  ];
+  const annotated = annotateZone(text, 'example.com', records);
+  const parsed = parseAnnotatedZone(annotated, 'example.com').records;
+  assert.deepStrictEqual(parsed[0].annotation, {
+    proxied: true,
+    comment: 'Main site, "prod"; do not remove',
+    tags: 'env:prod,team:web',
+  });
+  assert.strictEqual(parsed[0].content, '192.0.2.1');
+});